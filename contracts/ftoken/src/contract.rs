@@ -16,7 +16,7 @@ use crate::rand::sha_256;
 use crate::receiver::Snip20ReceiveMsg;
 use crate::state::{
     get_receiver_hash, read_allowance, read_viewing_key, set_receiver_hash, write_allowance,
-    write_viewing_key, Balances, Config, Constants, ReadonlyBalances, ReadonlyConfig,    
+    write_viewing_key, index_holder, Balances, Config, Constants, ReadonlyBalances, ReadonlyConfig,    
 };
 
 use crate::transaction_history::{
@@ -31,10 +31,13 @@ use secret_toolkit::permit::{validate, RevokedPermits};
 use crate::{
     ftoken_mod::{
         handles::{
-            add_ftoken_init, try_batch_receive_nft, try_propose, try_stake, try_unstake,
+            add_ftoken_init, try_batch_receive_nft, try_propose, try_stake, try_unstake, try_claim_unbonded,
             try_finalize_vote_may_execute_proposal, try_retrieve_prop_stake,
-            try_vote_resv_price, try_bid, try_vote_proposal, try_finalize_auction,
-            try_claim_proceeds, try_retrieve_bid,
+            try_vote_resv_price, try_expire_resv_votes, try_bid, try_vote_proposal, try_finalize_auction,
+            try_claim_proceeds, try_distribute_proceeds, try_retrieve_bid, try_refund_losing_bids, try_delegate, try_receive_snip20,
+            try_treasury_deposit, try_claim_slashed_stake, try_cancel_proposal, try_set_vault_status,
+            try_make_tender_offer, try_tender, try_settle_tender_offer, try_make_buyout_offer,
+            try_withdraw_buyout_offer, try_receive_ftokens, record_orphaned_ftokens,
             },
         queries::{ftoken_queries, ftoken_permit_queries}, //debug_query
     }
@@ -86,6 +89,7 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
             let amount = balance.amount.u128();
             let mut balances = Balances::from_storage(&mut deps.storage);
             balances.set_account_balance(&balance_address, amount);
+            index_holder(&mut deps.storage, &balance_address)?;
             if let Some(new_total_supply) = total_supply.checked_add(amount) {
                 total_supply = new_total_supply;
             } else {
@@ -292,13 +296,13 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             env,
             amount,    
         ),
-        // HandleMsg::Receive(
-        //     snip20receivemsg
-        // ) => try_receive_snip20(
-        //     deps,
-        //     env,
-        //     snip20receivemsg,
-        // ),
+        HandleMsg::Receive(
+            snip20receivemsg
+        ) => try_receive_snip20(
+            deps,
+            env,
+            snip20receivemsg,
+        ),
         HandleMsg::Stake {
             amount,
         } => try_stake(
@@ -313,6 +317,10 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             env,
             amount,
         ),
+        HandleMsg::ClaimUnbonded {  } => try_claim_unbonded(
+            deps,
+            env,
+        ),
         HandleMsg::VoteProposal {
             prop_id,
             vote,
@@ -349,17 +357,37 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             deps, 
             env,
         ),
+        HandleMsg::RefundLosingBids {
+            limit,
+        } => try_refund_losing_bids(
+            deps,
+            env,
+            limit,
+        ),
         HandleMsg::ClaimProceeds {
         } => try_claim_proceeds(
             deps,
             env,
         ),
+        HandleMsg::DistributeProceeds {
+            limit,
+        } => try_distribute_proceeds(
+            deps,
+            env,
+            limit,
+        ),
         HandleMsg::Propose {
+            title,
+            description,
+            link,
             proposal,
             stake,
         } => try_propose(
             deps,
             env,
+            title,
+            description,
+            link,
             proposal,
             stake,
         ),
@@ -384,6 +412,93 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             env,
             resv_price,
         ),
+        HandleMsg::ExpireResvVotes {
+            limit,
+        } => try_expire_resv_votes(
+            deps,
+            env,
+            limit,
+        ),
+        HandleMsg::Delegate {
+            proposals,
+            resv_price,
+        } => try_delegate(
+            deps,
+            env,
+            proposals,
+            resv_price,
+        ),
+        HandleMsg::TreasuryDeposit {
+            amount,
+        } => try_treasury_deposit(
+            deps,
+            env,
+            amount,
+        ),
+        HandleMsg::ClaimSlashedStake {
+            prop_id,
+        } => try_claim_slashed_stake(
+            deps,
+            env,
+            prop_id,
+        ),
+        HandleMsg::CancelProposal {
+            prop_id,
+        } => try_cancel_proposal(
+            deps,
+            env,
+            prop_id,
+        ),
+        HandleMsg::SetVaultStatus {
+            status,
+        } => try_set_vault_status(
+            deps,
+            env,
+            status,
+        ),
+        HandleMsg::MakeTenderOffer {
+            amount,
+            price,
+            period,
+        } => try_make_tender_offer(
+            deps,
+            env,
+            amount,
+            price,
+            period,
+        ),
+        HandleMsg::Tender {
+            offer_id,
+            amount,
+        } => try_tender(
+            deps,
+            env,
+            offer_id,
+            amount,
+        ),
+        HandleMsg::SettleTenderOffer {
+            offer_id,
+        } => try_settle_tender_offer(
+            deps,
+            env,
+            offer_id,
+        ),
+        HandleMsg::MakeBuyoutOffer {
+            amount,
+            period,
+        } => try_make_buyout_offer(
+            deps,
+            env,
+            amount,
+            period,
+        ),
+        HandleMsg::WithdrawBuyoutOffer {
+            offer_id,
+        } => try_withdraw_buyout_offer(
+            deps,
+            env,
+            offer_id,
+        ),
     };
 
     pad_response(response)
@@ -682,6 +797,7 @@ fn try_mint_impl<S: Storage>(
     }
 
     balances.set_account_balance(recipient, account_balance);
+    index_holder(storage, recipient)?;
 
     store_mint(storage, minter, recipient, amount, denom, memo, block)?;
 
@@ -923,6 +1039,7 @@ fn try_deposit<S: Storage, A: Api, Q: Querier>(
             "This deposit would overflow your balance",
         ));
     }
+    index_holder(&mut deps.storage, &sender_address)?;
 
     store_deposit(
         &mut deps.storage,
@@ -1050,6 +1167,7 @@ fn try_transfer<S: Storage, A: Api, Q: Querier>(
     memo: Option<String>,
 ) -> StdResult<HandleResponse> {
     let sender = deps.api.canonical_address(&env.message.sender)?;
+    record_orphaned_ftokens(&mut deps.storage, &recipient, amount)?;
     let recipient = deps.api.canonical_address(&recipient)?;
     try_transfer_impl(deps, &sender, &recipient, amount, memo, &env.block)?;
 
@@ -1068,6 +1186,7 @@ fn try_batch_transfer<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     let sender = deps.api.canonical_address(&env.message.sender)?;
     for action in actions {
+        record_orphaned_ftokens(&mut deps.storage, &action.recipient, action.amount)?;
         let recipient = deps.api.canonical_address(&action.recipient)?;
         try_transfer_impl(
             deps,
@@ -1131,6 +1250,7 @@ fn try_send_impl<S: Storage, A: Api, Q: Querier>(
     msg: Option<Binary>,
     block: &cosmwasm_std::BlockInfo,
 ) -> StdResult<()> {
+    record_orphaned_ftokens(&mut deps.storage, &recipient, amount)?;
     let recipient_canon = deps.api.canonical_address(&recipient)?;
     try_transfer_impl(
         deps,
@@ -1165,6 +1285,13 @@ fn try_send<S: Storage, A: Api, Q: Querier>(
     memo: Option<String>,
     msg: Option<Binary>,
 ) -> StdResult<HandleResponse> {
+    // ftokens sent to this contract with a msg are used in ftoken functionality, eg: staking
+    if recipient == env.contract.address {
+        if let Some(msg) = msg {
+            return try_receive_ftokens(deps, env, amount, msg)
+        }
+    }
+
     let mut messages = vec![];
     let sender = env.message.sender;
     let sender_canon = deps.api.canonical_address(&sender)?;
@@ -1307,6 +1434,7 @@ fn try_transfer_from<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     let spender = deps.api.canonical_address(&env.message.sender)?;
     let owner = deps.api.canonical_address(owner)?;
+    record_orphaned_ftokens(&mut deps.storage, recipient, amount)?;
     let recipient = deps.api.canonical_address(recipient)?;
     try_transfer_from_impl(deps, env, &spender, &owner, &recipient, amount, memo)?;
 
@@ -1326,6 +1454,7 @@ fn try_batch_transfer_from<S: Storage, A: Api, Q: Querier>(
     let spender = deps.api.canonical_address(&env.message.sender)?;
     for action in actions {
         let owner = deps.api.canonical_address(&action.owner)?;
+        record_orphaned_ftokens(&mut deps.storage, &action.recipient, action.amount)?;
         let recipient = deps.api.canonical_address(&action.recipient)?;
         try_transfer_from_impl(
             deps,
@@ -1362,6 +1491,7 @@ fn try_send_from_impl<S: Storage, A: Api, Q: Querier>(
     msg: Option<Binary>,
 ) -> StdResult<()> {
     let owner_canon = deps.api.canonical_address(&owner)?;
+    record_orphaned_ftokens(&mut deps.storage, &recipient, amount)?;
     let recipient_canon = deps.api.canonical_address(&recipient)?;
     try_transfer_from_impl(
         deps,
@@ -1771,10 +1901,30 @@ fn try_burn<S: Storage, A: Api, Q: Querier>(
     }
 
     let sender_address = deps.api.canonical_address(&env.message.sender)?;
+    try_burn_impl(&mut deps.storage, &sender_address, amount, memo, &env.block)?;
+
+    let res = HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Burn { status: Success })?),
+    };
+
+    Ok(res)
+}
+
+// ftoken addition: burn logic separated from `try_burn`, so ftoken functions can burn
+// tokens regardless of `burn_is_enabled`
+pub(crate) fn try_burn_impl<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    amount: Uint128,
+    memo: Option<String>,
+    block: &cosmwasm_std::BlockInfo,
+) -> StdResult<()> {
     let raw_amount = amount.u128();
 
-    let mut balances = Balances::from_storage(&mut deps.storage);
-    let mut account_balance = balances.balance(&sender_address);
+    let mut balances = Balances::from_storage(storage);
+    let mut account_balance = balances.balance(owner);
 
     if let Some(new_account_balance) = account_balance.checked_sub(raw_amount) {
         account_balance = new_account_balance;
@@ -1785,9 +1935,9 @@ fn try_burn<S: Storage, A: Api, Q: Querier>(
         )));
     }
 
-    balances.set_account_balance(&sender_address, account_balance);
+    balances.set_account_balance(owner, account_balance);
 
-    let mut config = Config::from_storage(&mut deps.storage);
+    let mut config = Config::from_storage(storage);
     let mut total_supply = config.total_supply();
     if let Some(new_total_supply) = total_supply.checked_sub(raw_amount) {
         total_supply = new_total_supply;
@@ -1798,23 +1948,18 @@ fn try_burn<S: Storage, A: Api, Q: Querier>(
     }
     config.set_total_supply(total_supply);
 
+    let symbol = config.constants()?.symbol;
     store_burn(
-        &mut deps.storage,
-        &sender_address,
-        &sender_address,
+        storage,
+        owner,
+        owner,
         amount,
-        constants.symbol,
+        symbol,
         memo,
-        &env.block,
+        block,
     )?;
 
-    let res = HandleResponse {
-        messages: vec![],
-        log: vec![],
-        data: Some(to_binary(&HandleAnswer::Burn { status: Success })?),
-    };
-
-    Ok(res)
+    Ok(())
 }
 
 fn perform_transfer<T: Storage>(
@@ -1841,6 +1986,7 @@ fn perform_transfer<T: Storage>(
         StdError::generic_err("This tx will literally make them too rich. Try transferring less")
     })?;
     balances.set_account_balance(to, to_balance);
+    index_holder(store, to)?;
 
     Ok(())
 }
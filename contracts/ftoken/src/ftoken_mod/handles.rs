@@ -1,7 +1,7 @@
 use std::{ops::{Add, Sub, },}; //Mul, Div 
 
 use cosmwasm_std::{
-    log, Api, Binary, Env, Extern, Uint128,
+    log, Api, Binary, Env, Extern, Uint128, LogAttribute,
     HandleResponse, HumanAddr, Querier, StdError,
     StdResult, Storage,
     from_binary, to_binary,
@@ -9,10 +9,10 @@ use cosmwasm_std::{
 };
 
 use crate::{
-    contract::{try_transfer_impl},
+    contract::{try_transfer_impl, try_burn_impl},
     msg::{InitMsg, HandleAnswer, ResponseStatus::Success,},
-    state::{Config, ReadonlyConfig, Balances, }, 
-    // receiver::Snip20ReceiveMsg,
+    state::{Config, ReadonlyConfig, ReadonlyBalances, may_get_holder, }, 
+    receiver::Snip20ReceiveMsg,
     ftoken_mod::{
         state::{
         prop_id_r, prop_id_w,
@@ -20,14 +20,28 @@ use crate::{
         ftoken_info_w, ftoken_info_r, props_w, props_r, add_bid, may_get_bid_from_addr,
         get_last_bid, set_bid,
         ftkn_stake_w, ftkn_stake_r, ftkn_config_w, ftkn_config_r,
-        votes_w, votes_r, votes_total_w, votes_total_r, 
+        votes_w, votes_r, votes_total_w, votes_total_r, vote_powers_w, vote_powers_r, 
+        vote_powers_total_w, vote_powers_total_r, 
         agg_resv_price_w, agg_resv_price_r, resv_price_w, resv_price_r,
         auction_info_w, auction_info_r,
+        delegs_w, delegs_r, deleg_power_w, deleg_power_r, prop_deleg_w, prop_deleg_r,
+        push_power_checkpoint, get_power_at, PowerCheckpoint, active_votes_w, active_votes_r, ActiveVote,
+        treasury_w, treasury_r, Treasury, TreasuryBalance, slash_claims_w, slash_claims_r, push_proposer_prop,
+        slash_claimed_w, slash_claimed_r,
+        vault_status_w, vault_status_r, VaultStatus,
+        tender_offers_w, tender_offers_r, tender_offer_id_w, tender_offer_id_r, TenderOffer,
+        buyout_offers_w, buyout_offers_r, buyout_offer_id_w, buyout_offer_id_r, BuyoutOffer,
+        sale_proceeds_w, sale_proceeds_r, SaleProceeds, resv_median_r, add_resv_stake, remove_resv_stake,
+        push_resv_checkpoint, ResvCheckpoint, resv_vote_heights_w, resv_vote_heights_r,
+        resv_vote_cursor_w, resv_vote_cursor_r, push_resv_vote_entry, may_get_resv_vote_entry, ResvVoteEntry,
+        unbonding_w, unbonding_r, UnbondingEntry, proceeds_claims_w, proceeds_claims_r, get_proposer_props,
+        proceeds_cursor_w, proceeds_cursor_r, bid_refund_cursor_w, bid_refund_cursor_r, may_get_bid,
+        orphaned_ftkn_w, orphaned_ftkn_r,
         PropInfo, StakedTokens, Vote, VoteRegister, VoteResult,
-        ResvVote, AuctionInfo, BidInfo,
+        ResvVote, AuctionInfo, BidInfo, Delegation, DelegatedPower, PropDelegTally,
         U256, 
         },
-        msg::{InitRes, Proposal, AllowedNftMsg, S721HandleMsg, S721QueryMsg},
+        msg::{InitRes, Proposal, Payout, AllowedNftMsg, S721HandleMsg, S721QueryMsg, FtokenReceiveMsg, ProposalVote},
     }, 
     viewing_key::ViewingKey, 
 };
@@ -38,12 +52,25 @@ use secret_toolkit::{
     crypto::sha_256,
 };
 use fsnft_utils::{
-    UndrNftInfo, FtokenInfo, FtokenConf, InterContrMsg,
+    UndrNftInfo, FtokenInfo, FtokenConf, InterContrMsg, VoteScheme, SlashDest, VoteThresholds, PropConf, ContractInfo,
+    ResvAgg,
     send_nft_msg,
 };
 
 use super::state::U384; 
 
+/// max length of proposal titles, in characters
+pub const MAX_PROP_TITLE_LEN: usize = 64;
+/// max length of proposal descriptions, in characters
+pub const MAX_PROP_DESC_LEN: usize = 1024;
+/// max length of proposal links, in characters
+pub const MAX_PROP_LINK_LEN: usize = 256;
+/// max number of queued reservation price votes that are checked for expiry when the reservation 
+/// price is next used. More can be cleared with `ExpireResvVotes`
+pub const MAX_LAZY_RESV_EXPIRIES: u32 = 20;
+/// max number of unbonding entries that an address can have at any time
+pub const MAX_UNBONDING_ENTRIES: usize = 7;
+
 
 
 
@@ -57,14 +84,25 @@ pub fn add_ftoken_init<S: Storage, A: Api, Q: Querier>(
     env: Env,
     msg: InitMsg,
 ) -> StdResult<Vec<CosmosMsg>> {
+    msg.init_info.ftkn_conf.prop_conf.validate_thresholds()?;
+
     // init bid_id = 0, and save allowed bid token
     prop_id_w(&mut deps.storage).save(&0u32)?;
     ftkn_config_w(&mut deps.storage).save(&msg.init_info.ftkn_conf)?;
     auction_info_w(&mut deps.storage).save(&AuctionInfo::init())?;
+    vault_status_w(&mut deps.storage).save(&VaultStatus::Normal)?;
+    tender_offer_id_w(&mut deps.storage).save(&0u32)?;
+    buyout_offer_id_w(&mut deps.storage).save(&0u32)?;
+    resv_vote_cursor_w(&mut deps.storage).save(&0u32)?;
     agg_resv_price_w(&mut deps.storage).save(&ResvVote::new(
         Uint128(0),
         msg.init_info.init_resv_price,
     ))?;
+    push_resv_checkpoint(&mut deps.storage, &ResvCheckpoint {
+        height: env.block.height,
+        price: msg.init_info.init_resv_price,
+        stake: Uint128(0),
+    })?;
 
     // InitResponse to fractionalizer contract to register this ftoken contract
     let reg_msg = InitRes::register_receive(msg.clone(), env.clone());
@@ -85,6 +123,18 @@ pub fn add_ftoken_init<S: Storage, A: Api, Q: Querier>(
     };
     ftoken_info_w(&mut deps.storage).save(&ftoken_info)?;
 
+    // register bid token with the vault treasury, so ftoken contract can receive it
+    let bid_token = msg.init_info.ftkn_conf.auc_conf.bid_token.clone();
+    let cosmos_msg_reg_bid_token = snip20_register_receive_msg(
+        env.contract_code_hash.clone(),
+        bid_token.address.clone(),
+        bid_token.code_hash.clone(),
+    )?;
+    treasury_w(&mut deps.storage).save(&Treasury {
+        snip20s: vec![TreasuryBalance { token: bid_token, balance: Uint128(0), distributed: Uint128(0) }],
+        ftoken: Uint128(0),
+    })?;
+
     // set viewing key. Alternatively use query permits, but some older NFTs may not implement query permits
     // created prng_seed_hashed twice. Might save gas to create only once, but likely marginal
     // for greater security, use Secret Orcales (Scrt-RNG) to generate random numbers
@@ -102,6 +152,7 @@ pub fn add_ftoken_init<S: Storage, A: Api, Q: Querier>(
     let messages = vec![
         cosmos_msg_reg,
         cosmos_msg_setvk,
+        cosmos_msg_reg_bid_token,
     ];
 
     Ok(messages)
@@ -109,13 +160,17 @@ pub fn add_ftoken_init<S: Storage, A: Api, Q: Querier>(
 
 
 /// Stake ftokens so ftoken holder can vote on reservation price or proposals.
-/// If user has already voted, they will need to vote again after staking for the new
-/// tokens to count towards their votes
+/// Existing votes are re-weighted automatically: an existing reservation price vote counts the 
+/// new tokens immediately, while votes on live proposals are capped at the voting power at the 
+/// proposal's snapshot height. If user has delegated its votes, the new tokens are added to 
+/// its delegates' voting power
 pub fn try_stake<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     amount: Uint128,
 ) -> StdResult<HandleResponse> {
+    check_vault_status(&deps.storage, VaultOp::Other)?;
+
     // transfer ftokens
     try_transfer_impl(
         deps, 
@@ -135,16 +190,22 @@ pub fn try_stake<S: Storage, A: Api, Q: Querier>(
     };
     let new_amount = Uint128(ftkn_stake.amount.u128().checked_add(amount.u128()).unwrap());
 
-    // calculate new unlock height: max of current or min bond period
-    let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
-    let new_unlock_height = ftkn_stake.unlock_height.max(env.block.height + ftkn_conf.min_ftkn_bond_prd);
-    
+    // staking does not bond ftokens, as the bond period applies when ftokens are unstaked
     let staked_tokens = StakedTokens{
         amount: new_amount,
-        unlock_height: new_unlock_height,
+        unlock_height: ftkn_stake.unlock_height,
     };
     ftkn_stake_w(&mut deps.storage).save(to_binary(&env.message.sender)?.as_slice(), &staked_tokens)?;
 
+    // add to voting power of delegates
+    adj_deleg_power(&mut deps.storage, &env.message.sender, amount, Uint128(0), env.block.height)?;
+    checkpoint_power(&mut deps.storage, &env.message.sender, env.block.height)?;
+
+    // re-weight existing votes
+    reweigh_prop_votes(&mut deps.storage, &env.message.sender, env.block.height)?;
+    reweigh_resv_vote(&mut deps.storage, &env.message.sender)?;
+    checkpoint_resv_price(&mut deps.storage, env.block.height)?;
+
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
@@ -152,53 +213,99 @@ pub fn try_stake<S: Storage, A: Api, Q: Querier>(
     })
 }
 
-/// Unstake ftokens. 
-/// Unlike staking, unstaking ftokens automatically changes the aggregate vote results on `reservation price`.
-/// Note that no change in proposal vote is required, because ftokens are bonded at least until end of proposal
-/// voting periods 
+/// Handles ftokens sent to this contract with a `Send` message that has a `msg` attached, 
+/// which determines what the ftokens are used for
+pub fn try_receive_ftokens<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Uint128,
+    msg: Binary,
+) -> StdResult<HandleResponse> {
+    match from_binary(&msg)? {
+        FtokenReceiveMsg::Stake { proposal_vote, resv_price } => {
+            try_stake(deps, env.clone(), amount)?;
+            if let Some(ProposalVote { prop_id, vote }) = proposal_vote {
+                try_vote_proposal(deps, env.clone(), prop_id, vote)?;
+            }
+            if let Some(resv_price) = resv_price {
+                try_vote_resv_price(deps, env, resv_price)?;
+            }
+        },
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Send { status: Success })?),
+    })
+}
+
+/// Unstake ftokens into a new unbonding entry, which can be claimed with `ClaimUnbonded` once it 
+/// matures. The entry matures after the bond period, or when the sender's (or its delegate's) votes 
+/// stop bonding its ftokens, whichever is later. Once the vault is no longer active, ftokens are 
+/// returned immediately. 
+/// Existing votes on `reservation price` and on proposals still in their voting period are re-weighted 
+/// automatically (including those of delegates)
 pub fn try_unstake<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     amount: Uint128,
 ) -> StdResult<HandleResponse> {
-    // check if ftokens are still bonded
-    let ftkn_stake_op = ftkn_stake_r(&deps.storage).may_load(to_binary(&env.message.sender)?.as_slice())?;
+    let sender = to_binary(&env.message.sender)?;
+    let sender_u8 = sender.as_slice();
+    let ftkn_stake_op = ftkn_stake_r(&deps.storage).may_load(sender_u8)?;
     let ftkn_stake = match ftkn_stake_op {
         Some(i) => i,
         None => return Err(StdError::generic_err("this address has not staked ftokens before"))
     };
-    if env.block.height < ftkn_stake.unlock_height {
-        return Err(StdError::generic_err(format!("ftokens are still bonded. Will unbond at height {}", ftkn_stake.unlock_height)))
-    }
+    let new_amount = match ftkn_stake.amount.sub(amount) {
+        Ok(i) => i,
+        Err(_) => return Err(StdError::generic_err(format!(
+            "insufficient staked ftokens: staked={}, required={}", ftkn_stake.amount, amount
+        ))),
+    };
 
-    // transfer ftokens
-    try_transfer_impl(
-        deps, 
-        &deps.api.canonical_address(&env.contract.address)?,
-        &deps.api.canonical_address(&env.message.sender)?,
-        amount,
-        None,
-        &env.block,
-    )?;
+    if ftoken_info_r(&deps.storage).load()?.vault_active {
+        // delegated ftokens are also bonded while the delegate's proposal votes are live
+        let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
+        let mature_height = env.block.height.saturating_add(ftkn_conf.min_ftkn_bond_prd)
+            .max(ftkn_stake.unlock_height)
+            .max(deleg_unlock_height(&deps.storage, &env.message.sender)?);
+
+        let mut entries = unbonding_r(&deps.storage).may_load(sender_u8)?.unwrap_or_default();
+        if entries.len() >= MAX_UNBONDING_ENTRIES {
+            return Err(StdError::generic_err(format!(
+                "cannot have more than {} unbonding entries. Claim matured entries first", MAX_UNBONDING_ENTRIES
+            )))
+        }
+        entries.push(UnbondingEntry { amount, mature_height });
+        unbonding_w(&mut deps.storage).save(sender_u8, &entries)?;
+    } else {
+        try_transfer_impl(
+            deps, 
+            &deps.api.canonical_address(&env.contract.address)?,
+            &deps.api.canonical_address(&env.message.sender)?,
+            amount,
+            None,
+            &env.block,
+        )?;
+    }
 
     // save info after unstake `amount` of ftokens 
-    let new_amount = ftkn_stake.amount.sub(amount)?;
     let staked_tokens = StakedTokens{
         amount: new_amount,
         unlock_height: ftkn_stake.unlock_height,
     };
     ftkn_stake_w(&mut deps.storage).save(to_binary(&env.message.sender)?.as_slice(), &staked_tokens)?;
 
-    // change reservation votes changes to storage
-    let curr_agg_resv = agg_resv_price_r(&deps.storage).load()?;
-    let old_resv = resv_price_r(&deps.storage).load(&to_binary(&env.message.sender)?.as_slice())?;
-    let new_resv = ResvVote::new(
-        old_resv.uint128_stake().sub(amount)?,
-        old_resv.uint128_price(),
-    );
-    resv_price_w(&mut deps.storage).save(&to_binary(&env.message.sender)?.as_slice(), &new_resv)?;
-    let new_agg_resv = new_agg_resv_vote(&curr_agg_resv, &old_resv, &new_resv);
-    agg_resv_price_w(&mut deps.storage).save(&new_agg_resv)?;
+    // remove from voting power of delegates
+    adj_deleg_power(&mut deps.storage, &env.message.sender, Uint128(0), amount, env.block.height)?;
+    checkpoint_power(&mut deps.storage, &env.message.sender, env.block.height)?;
+
+    // re-weight existing votes
+    reweigh_prop_votes(&mut deps.storage, &env.message.sender, env.block.height)?;
+    reweigh_resv_vote(&mut deps.storage, &env.message.sender)?;
+    checkpoint_resv_price(&mut deps.storage, env.block.height)?;
     
     Ok(HandleResponse {
         messages: vec![],
@@ -207,6 +314,41 @@ pub fn try_unstake<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Returns ftokens in unbonding entries that have matured to the sender. Once the vault is no 
+/// longer active, all unbonding entries can be claimed
+pub fn try_claim_unbonded<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    let sender = to_binary(&env.message.sender)?;
+    let sender_u8 = sender.as_slice();
+    let vault_active = ftoken_info_r(&deps.storage).load()?.vault_active;
+    let (matured, unbonding): (Vec<UnbondingEntry>, Vec<UnbondingEntry>) = unbonding_r(&deps.storage)
+        .may_load(sender_u8)?.unwrap_or_default()
+        .into_iter()
+        .partition(|entry| !vault_active || entry.mature_height <= env.block.height);
+    if matured.is_empty() {
+        return Err(StdError::generic_err("no unbonding ftokens have matured"))
+    }
+    unbonding_w(&mut deps.storage).save(sender_u8, &unbonding)?;
+
+    let amount = Uint128(matured.iter().map(|entry| entry.amount.u128()).sum());
+    try_transfer_impl(
+        deps, 
+        &deps.api.canonical_address(&env.contract.address)?,
+        &deps.api.canonical_address(&env.message.sender)?,
+        amount,
+        None,
+        &env.block,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ClaimUnbonded { status: Success })?),
+    })
+}
+
 /// handles ftoken holder votes on proposals
 pub fn try_vote_proposal<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -216,37 +358,50 @@ pub fn try_vote_proposal<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     // check if proposal exists
     let prop_info_op = props_r(&deps.storage).may_load(&prop_id.to_le_bytes())?;
-    let prop_info = match prop_info_op {
+    let mut prop_info = match prop_info_op {
         Some(i) => i,
         None => return Err(StdError::generic_err("proposal id refers to a non-existent proposal")),
     };
+    check_prop_vault_status(&deps.storage, &prop_info.proposal)?;
+    prop_info.cancel_if_vault_closed(ftoken_info_r(&deps.storage).load()?.vault_active);
 
     // check if proposal is still in voting period
     if prop_info.end_height < env.block.height {
         return Err(StdError::generic_err("proposal voting period has ended"))
     }
+    if prop_info.outcome == Some(VoteResult::Cancelled) {
+        return Err(StdError::generic_err("proposal has been cancelled"))
+    }
 
-    // load staked ftokens of sender
+    // load staked ftokens of sender, and voting power at the proposal's snapshot height
     let sender = to_binary(&env.message.sender)?;
     let sender_u8 = sender.as_slice();
-    let mut ftkn_stake = ftkn_stake_r(&deps.storage).load(sender_u8)?;
+    let mut ftkn_stake = ftkn_stake_r(&deps.storage).may_load(sender_u8)?.unwrap_or_default();
+    let snapshot = get_power_at(&deps.storage, &env.message.sender, prop_info.snapshot_height)?;
 
     // check if sender has voted before
-    let vote_op = votes_r(&deps.storage, prop_id).may_load(to_binary(&env.message.sender)?.as_slice())?;
-    let old_vote_reg = match vote_op {
-        Some(i) => i,
-        None => VoteRegister::default(),
-    };
+    let vote_op = votes_r(&deps.storage, prop_id).may_load(sender_u8)?;
 
-    // save new vote
-    let mut new_vote_reg = VoteRegister::default();
-    match vote {
-        Vote::Yes => new_vote_reg.yes = ftkn_stake.amount,
-        Vote::No => new_vote_reg.no = ftkn_stake.amount,
-        Vote::Veto => new_vote_reg.veto = ftkn_stake.amount,
-        Vote::Abstain => new_vote_reg.abstain = ftkn_stake.amount,
-    };
-    votes_w(&mut deps.storage, prop_id).save(sender_u8, &new_vote_reg)?;
+    // check that sender has voting power on this proposal, from own or delegated ftokens
+    let (power, _) = prop_vote_weight(&deps.storage, &env.message.sender, &prop_info)?;
+    if power == Uint128(0) {
+        return Err(StdError::generic_err(format!(
+            "no voting power on this proposal: ftokens must be staked or delegated to you at block height {}",
+            prop_info.snapshot_height,
+        )))
+    }
+
+    // save new vote, and net effect on (cumulative) vote total tally. Vote is indexed, so it can be 
+    // re-weighted when sender's voting power changes
+    let mut active_votes = load_active_votes(&deps.storage, &env.message.sender, env.block.height)?;
+    let prev_vote = active_votes.iter()
+        .position(|active_vote| active_vote.prop_id == prop_id)
+        .map(|idx| active_votes.remove(idx));
+    let active_vote = save_prop_vote(
+        &mut deps.storage, &env.message.sender, &prop_info, &vote, env.block.height, prev_vote.as_ref()
+    )?;
+    active_votes.push(active_vote);
+    active_votes_w(&mut deps.storage).save(sender_u8, &active_votes)?;
 
     // update staked ftoken bonded period to max of i) current unlock height, ii) min bond period, iii) end of voting period for bid user just voted for 
     let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
@@ -257,19 +412,10 @@ pub fn try_vote_proposal<S: Storage, A: Api, Q: Querier>(
     );
     ftkn_stake_w(&mut deps.storage).save(sender_u8, &ftkn_stake)?;
 
-    // adjust aggregate vote
-    // load total votes
-    let mut votes_total = votes_total_r(&deps.storage).load(&prop_id.to_le_bytes())?;
-
-    // save net effect on (cumulative) vote total tally
-    votes_total.yes = Uint128(votes_total.yes.u128().checked_add(new_vote_reg.yes.u128()).unwrap())
-                        .sub(old_vote_reg.yes).unwrap();
-    votes_total.no = Uint128(votes_total.no.u128().checked_add(new_vote_reg.no.u128()).unwrap())
-                        .sub(old_vote_reg.no).unwrap();
-    votes_total.veto = Uint128(votes_total.veto.u128().checked_add(new_vote_reg.veto.u128()).unwrap())
-                        .sub(old_vote_reg.veto).unwrap();
-    
-    votes_total_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &votes_total).unwrap();
+    // a delegator's first direct vote on this proposal overrides its delegate at the snapshot height
+    if let (None, Some(delegate)) = (vote_op, snapshot.delegate) {
+        override_delegate_vote(&mut deps.storage, &prop_info, &delegate, snapshot.staked, env.block.height)?;
+    }
 
     Ok(HandleResponse {
         messages: vec![],
@@ -280,11 +426,17 @@ pub fn try_vote_proposal<S: Storage, A: Api, Q: Querier>(
 
 
 /// # Arguments
+/// * `title`, `description`, `link` - proposal metadata, up to `MAX_PROP_TITLE_LEN`, 
+///   `MAX_PROP_DESC_LEN` and `MAX_PROP_LINK_LEN` characters respectively
 /// * `stake` - the ftoken stake required when making a proposal. This can be retrieved
-/// after the voting period, unless the result is no_with_veto
+/// after the voting period, unless the result is no_with_veto, in which case the stake is
+///   slashed according to the proposal config's `slash_dest`
 pub fn try_propose<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
+    title: String,
+    description: String,
+    link: Option<String>,
     proposal: Proposal,
     stake: Uint128,
 ) -> StdResult<HandleResponse> {
@@ -293,6 +445,14 @@ pub fn try_propose<S: Storage, A: Api, Q: Querier>(
     if ftkn_info.vault_active == false {
         return Err(StdError::generic_err("nft no longer in vault"))
     };
+    check_prop_vault_status(&deps.storage, &proposal)?;
+
+    // check proposal metadata lengths
+    check_len("title", &title, MAX_PROP_TITLE_LEN)?;
+    check_len("description", &description, MAX_PROP_DESC_LEN)?;
+    if let Some(link) = &link {
+        check_len("link", link, MAX_PROP_LINK_LEN)?;
+    }
 
     // check that ftoken stake is adequate
     let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
@@ -303,9 +463,54 @@ pub fn try_propose<S: Storage, A: Api, Q: Querier>(
         )));
     }  
 
-    match proposal {
-        Proposal::MsgToNft { .. } => (),
-        Proposal::ChangeConfig { ..} => (),
+    match &proposal {
+        Proposal::MsgToNft { msg } => {
+            if ftkn_conf.prop_conf.nft_msg_policy(&msg.msg_type()).is_none() {
+                return Err(StdError::generic_err("this message to the underlying NFT is disabled"))
+            }
+        },
+        Proposal::ChangeConfig { config } => config.prop_conf.validate_thresholds()?,
+        Proposal::RegisterTreasuryToken { token } => {
+            let treasury = treasury_r(&deps.storage).load()?;
+            if treasury.snip20s.iter().any(|i| i.token.address == token.address) {
+                return Err(StdError::generic_err("token is already registered with the vault treasury"))
+            }
+        },
+        Proposal::TreasurySpend { token, payouts } => {
+            let treasury = treasury_r(&deps.storage).load()?;
+            let balance = treasury_spend_balance(&treasury, token)?;
+            let total = payouts.iter().map(|i| i.amount.u128()).sum::<u128>();
+            if total > balance.u128() {
+                return Err(StdError::generic_err(format!(
+                    "insufficient treasury balance: {} available", balance
+                )))
+            }
+        },
+        Proposal::RotateViewingKey {  } => (),
+        Proposal::RevokePermits { permit_names } => {
+            if permit_names.is_empty() {
+                return Err(StdError::generic_err("no permits to revoke"))
+            }
+        },
+        Proposal::SetVaultStatus { .. } => (),
+        Proposal::AcceptOffer { offer_id } => {
+            let offer = load_buyout_offer(&deps.storage, *offer_id)?;
+            if offer.withdrawn {
+                return Err(StdError::generic_err("buyout offer has been withdrawn"))
+            }
+            if offer.end_height < env.block.height + ftkn_conf.prop_conf.vote_period {
+                return Err(StdError::generic_err(format!(
+                    "buyout offer expires at height {}, before the proposal voting period ends", offer.end_height
+                )))
+            }
+            if let Some(pending) = offer.prop_id {
+                if props_r(&deps.storage).load(&pending.to_le_bytes())?.outcome.is_none() {
+                    return Err(StdError::generic_err(format!(
+                        "proposal {} to accept this buyout offer is pending", pending
+                    )))
+                }
+            }
+        },
     };
 
     // load current prop_id
@@ -314,11 +519,19 @@ pub fn try_propose<S: Storage, A: Api, Q: Querier>(
     let prop_info = PropInfo {
         prop_id,
         proposer: env.message.sender.clone(),
+        title,
+        description,
+        link,
         proposal,
         stake,
         stake_withdrawn: false,
+        executed: false,
         outcome: None, 
         end_height: env.block.height + ftkn_conf.prop_conf.vote_period,
+        snapshot_height: env.block.height,
+        private_votes: ftkn_conf.prop_conf.private_votes,
+        vote_scheme: ftkn_conf.prop_conf.vote_scheme.clone(),
+        slash_dest: ftkn_conf.prop_conf.slash_dest.clone(),
     };
 
     // transfer ftoken stake to contract
@@ -333,9 +546,16 @@ pub fn try_propose<S: Storage, A: Api, Q: Querier>(
 
     // Note that prop_id: u32 implements copy, hence no borrowing issues here
     props_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &prop_info)?;
+    if let Proposal::AcceptOffer { offer_id } = &prop_info.proposal {
+        let mut offer = load_buyout_offer(&deps.storage, *offer_id)?;
+        offer.prop_id = Some(prop_id);
+        buyout_offers_w(&mut deps.storage).save(&offer_id.to_le_bytes(), &offer)?;
+    }
+    push_proposer_prop(&mut deps.storage, &env.message.sender, prop_id)?;
 
     // initialize votes_total to 0
     votes_total_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &VoteRegister::default())?;
+    vote_powers_total_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &VoteRegister::default())?;
 
     // add 1 to bid_id count
     prop_id_w(&mut deps.storage).save(&prop_id.add(1u32))?;
@@ -358,15 +578,25 @@ pub fn try_bid<S: Storage, A: Api, Q: Querier>(
     if ftkn_info.vault_active == false {
         return Err(StdError::generic_err("vault no longer active"))
     };
+    check_vault_status(&deps.storage, VaultOp::Auction)?;
 
     // load SNIP20 token ContractInfo and auction status
     let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
     let mut auc_info = auction_info_r(&deps.storage).load()?;
+    let mut refund = None;
     
     // if auction is not yet live
     if auc_info.is_active == false {
+        // only live reservation price votes count towards the reservation price and unlock threshold
+        if !expire_resv_votes(&mut deps.storage, env.block.height, MAX_LAZY_RESV_EXPIRIES)? {
+            return Err(StdError::generic_err(
+                "too many expired reservation price votes. Clear them with `expire_resv_votes` before bidding"
+            ))
+        }
+
         // error if bid is below reservation price
         let resv_info = agg_resv_price_r(&deps.storage).load()?;
+        let resv_price = curr_resv_price(&deps.storage, &ftkn_conf.auc_conf.resv_agg)?;
         
         let config = Config::from_storage(&mut deps.storage);
         let curr_staked_bp = calc_pro_rata(
@@ -375,9 +605,9 @@ pub fn try_bid<S: Storage, A: Api, Q: Querier>(
             10_000_u128
         )?;  
 
-        if amount < resv_info.uint128_price() {
+        if amount < resv_price {
             return Err(StdError::generic_err(format!(
-                "bid must be equal or greater than the reservation price of {}", resv_info.uint128_price()
+                "bid must be equal or greater than the reservation price of {}", resv_price
             )))
         // error if vault is not yet `unlocked` ie: haven't reached threshold number of reservation votes
         } else if curr_staked_bp < ftkn_conf.auc_conf.unlock_threshold.u128() {
@@ -395,7 +625,7 @@ pub fn try_bid<S: Storage, A: Api, Q: Querier>(
         }
     // if auction is already live
     } else if auc_info.is_active == true {
-        let (last_bid, _) = get_last_bid(&deps.storage)?;
+        let (mut last_bid, last_pos) = get_last_bid(&deps.storage)?;
         let min_bid = last_bid.amount
             .multiply_ratio(Uint128(auc_info.auc_config_snapshot.min_bid_inc.add(10_000) as u128), Uint128(10_000));
         // check that new bid is higher than the min_bid = highest_bid x min_bid_increment    
@@ -407,16 +637,29 @@ pub fn try_bid<S: Storage, A: Api, Q: Querier>(
             )))
         // check that auction has not closed (current block height has not passed end height)
         }
+
+        // refund the previous highest bidder, unless they are raising their own bid
+        if auc_info.auc_config_snapshot.refund_outbid && last_bid.bidder != env.message.sender {
+            refund = Some(snip20_transfer_msg(
+                last_bid.bidder.clone(), 
+                last_bid.amount, 
+                auc_info.auc_config_snapshot.bid_token.address.clone(), 
+                auc_info.auc_config_snapshot.bid_token.code_hash.clone(),
+            )?);
+            last_bid.retrieved_bid = true;
+            set_bid(&mut deps.storage, last_pos, &last_bid)?;
+        }
     } else { return Err(StdError::generic_err("this should not happen")) }
 
     // check that bidder has bid before -> if so, update_bid with incremental amount instead,
+    // unless the previous bid has already been refunded
     let prev_bid_op = may_get_bid_from_addr(
         &deps.storage, 
         &env.message.sender
     )?;
     let transfer_amount = match prev_bid_op {
-        Some((prev_bid, _)) => amount.sub(prev_bid.amount)?,
-        None => amount,
+        Some((prev_bid, _)) if !prev_bid.retrieved_bid => amount.sub(prev_bid.amount)?,
+        _ => amount,
     };
 
     // create `TransferFrom` msg to send to SNIP20 ("sSCRT") contract
@@ -428,7 +671,8 @@ pub fn try_bid<S: Storage, A: Api, Q: Querier>(
         auc_info.auc_config_snapshot.bid_token.code_hash
     )?;
     
-    let messages = vec![message];
+    let mut messages = vec![message];
+    messages.extend(refund);
 
     // save new bid at the top of the storage stack. If updating bid, this should replace 
     // the old link between bidder's HumanAddr and the pos: u32 when the `bids_w` function is called  
@@ -455,6 +699,7 @@ pub fn try_finalize_auction<S: Storage, A: Api, Q: Querier>(
     if ftkn_info.vault_active == false {
         return Err(StdError::generic_err("vault is no longer active"))
     };
+    check_vault_status(&deps.storage, VaultOp::Auction)?;
 
     // check that auction period is over
     let auc_info = auction_info_r(&deps.storage).load()?;
@@ -476,12 +721,18 @@ pub fn try_finalize_auction<S: Storage, A: Api, Q: Querier>(
     // save winning_bid.winning_bid = true
     winning_bid.winning_bid = true;
     set_bid(&mut deps.storage, pos, &winning_bid)?;
+    sale_proceeds_w(&mut deps.storage).save(&SaleProceeds {
+        token: auc_info.auc_config_snapshot.bid_token.clone(),
+        amount: winning_bid.amount,
+        claimed_ftokens: Uint128(0),
+        claimed: Uint128(0),
+    })?;
 
     // transfer nft to winning bidder    
     let winner = winning_bid.bidder;
     let send_nft_msg = send_nft_msg(
         deps, 
-        env, 
+        env.clone(), 
         ftkn_info.instance.init_nft_info.nft_contr.address.clone(), 
         ftkn_info.instance.init_nft_info.nft_contr.code_hash.clone(), 
         winner, 
@@ -489,7 +740,8 @@ pub fn try_finalize_auction<S: Storage, A: Api, Q: Querier>(
         None,
     )?;
 
-    // close vault: save state
+    // close vault: save state. Pending proposals can no longer be executed against the underlying
+    // NFT, and are treated as cancelled from here on
     ftkn_info.vault_active = false;
     ftoken_info_w(&mut deps.storage).save(&ftkn_info)?;
 
@@ -500,6 +752,8 @@ pub fn try_finalize_auction<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Finalizes vote count after the voting period, and executes the proposal if it won.
+/// For secret ballot proposals, this is also the point where the full tally is revealed
 pub fn try_finalize_vote_may_execute_proposal<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -507,37 +761,77 @@ pub fn try_finalize_vote_may_execute_proposal<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     let mut prop_info = props_r(&deps.storage).load(&prop_id.to_le_bytes())?;
 
-    // check if voting period is over
-    if env.block.height < prop_info.end_height {
-        return Err(StdError::generic_err("proposal still in voting"))
+    // check if proposal has already been finalized
+    if prop_info.outcome.is_some() {
+        return Err(StdError::generic_err("proposal has already been finalized"))
+    }
+
+    // proposals left pending when the vault closed are cancelled, and their stake refunded
+    if !ftoken_info_r(&deps.storage).load()?.vault_active {
+        cancel_prop(deps, &env, &mut prop_info)?;
+        return Ok(HandleResponse {
+            messages: vec![],
+            log: cancel_prop_log(&prop_info),
+            data: Some(to_binary(&HandleAnswer::FinalizeExecuteProp { status: Success })?),
+        })
     }
+    check_prop_vault_status(&deps.storage, &prop_info.proposal)?;
 
-    // finalize vote count
+    // finalize vote count. If voting period is not over, emergency proposals can be executed 
+    // early if `yes` votes reach the emergency threshold
     let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
-    let vote_result = determine_vote_res(
-        &mut deps.storage, 
-        prop_id, 
-        ftkn_conf.prop_conf.vote_quorum,
-        ftkn_conf.prop_conf.veto_threshold,
-    )?;
+    let vote_result = if env.block.height < prop_info.end_height {
+        let emergency = prop_info.proposal.is_emergency() && emergency_threshold_met(
+            &deps.storage,
+            prop_id,
+            ftkn_conf.prop_conf.emergency_threshold,
+        )?;
+        if !emergency {
+            return Err(StdError::generic_err("proposal still in voting"))
+        }
+        VoteResult::Won
+    } else {
+        determine_vote_res(
+            &mut deps.storage, 
+            prop_id, 
+            &prop_thresholds(&ftkn_conf.prop_conf, &prop_info.proposal),
+        )?
+    };
     
     // save vote result 
     prop_info.outcome = Some(vote_result);
-    props_w(&mut deps.storage).save(&prop_id.to_be_bytes(), &prop_info)?;
+    props_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &prop_info)?;
 
     // control flow depending on vote result
-    match prop_info.outcome {
+    let mut logs = vec![];
+    let messages = match prop_info.outcome.clone() {
         None => return Err(StdError::generic_err("this error message should not be reachable")),
         Some(vote_result) => match vote_result {
-            VoteResult::Won => try_execute_proposal(deps, env, prop_info.proposal)?,
-            VoteResult::Lost => (),
-            VoteResult::LostWithVeto => (),
+            VoteResult::Won => match try_execute_proposal(deps, env, prop_info.proposal.clone())? {
+                Some(messages) => {
+                    prop_info.executed = true;
+                    props_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &prop_info)?;
+                    messages
+                },
+                // the proposal passed, but can no longer be executed. It stays `Passed`
+                None => {
+                    logs.push(log("not_executed", prop_id));
+                    vec![]
+                },
+            },
+            VoteResult::Lost => vec![],
+            VoteResult::LostWithVeto => {
+                slash_prop_stake(deps, env, &mut prop_info)?;
+                props_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &prop_info)?;
+                vec![]
+            },
+            VoteResult::Cancelled => return Err(StdError::generic_err("this error message should not be reachable")),
         }
-    }
+    };
 
     Ok(HandleResponse {
-        messages: vec![],
-        log: vec![],
+        messages,
+        log: logs,
         data: Some(to_binary(&HandleAnswer::FinalizeExecuteProp { status: Success })?),
     })
 
@@ -638,6 +932,57 @@ pub fn try_retrieve_bid<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Refunds losing bids that have not been retrieved, walking the bids from where the last call left 
+/// off. Up to `limit` bids are processed per call. Anyone can call this once the vault has closed
+pub fn try_refund_losing_bids<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    _env: Env,  
+    limit: u32,
+) -> StdResult<HandleResponse> {
+    // check that vault is no longer live
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault is still active"))
+    }
+
+    let auc_info = auction_info_r(&deps.storage).load()?;
+    let mut cursor = bid_refund_cursor_r(&deps.storage).may_load()?.unwrap_or(0);
+    let mut messages = vec![];
+    for _ in 0..limit {
+        let mut bid_info = match may_get_bid(&deps.storage, cursor)? {
+            Some(i) => i,
+            None => break,
+        };
+        let pos = cursor;
+        cursor += 1;
+        if bid_info.winning_bid || bid_info.retrieved_bid {
+            continue
+        }
+        // a bidder that raised their bid has a new entry holding the full amount. Their 
+        // earlier entries are superseded, and are not refunded
+        let latest_pos = may_get_bid_from_addr(&deps.storage, &bid_info.bidder)?.map(|(_, pos)| pos);
+        if latest_pos != Some(pos) {
+            continue
+        }
+
+        messages.push(snip20_transfer_msg(
+            bid_info.bidder.clone(), 
+            bid_info.amount, 
+            auc_info.auc_config_snapshot.bid_token.address.clone(), 
+            auc_info.auc_config_snapshot.bid_token.code_hash.clone(),
+        )?);
+        bid_info.retrieved_bid = true;
+        set_bid(&mut deps.storage, pos, &bid_info)?;
+    }
+    bid_refund_cursor_w(&mut deps.storage).save(&cursor)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::RefundLosingBids { status: Success })?),
+    })
+}
+
 /// retreive proposal stake
 pub fn try_retrieve_prop_stake<S: Storage, A: Api, Q:Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -646,12 +991,23 @@ pub fn try_retrieve_prop_stake<S: Storage, A: Api, Q:Querier>(
 ) -> StdResult<HandleResponse> {
     // check that proposal status: vote result
     let mut prop_info = props_r(&deps.storage).load(&prop_id.to_le_bytes())?;
+    prop_info.cancel_if_vault_closed(ftoken_info_r(&deps.storage).load()?.vault_active);
     match prop_info.outcome {
         None => return Err(StdError::generic_err("proposal has not been finalized and executed")),
         Some(ref vote_result) => match vote_result {
             VoteResult::Won => (),
             VoteResult::Lost => (),
             VoteResult::LostWithVeto => return Err(StdError::generic_err("stake lost because proposal result: no with veto")),
+            VoteResult::Cancelled if prop_info.stake_withdrawn => return Err(StdError::generic_err("proposal was cancelled, and its stake refunded to the proposer")),
+            // cancelled when the vault closed: refund the stake to the proposer
+            VoteResult::Cancelled => {
+                cancel_prop(deps, &env, &mut prop_info)?;
+                return Ok(HandleResponse {
+                    messages: vec![],
+                    log: cancel_prop_log(&prop_info),
+                    data: Some(to_binary(&HandleAnswer::RetrievePropStake { status: Success })?),
+                })
+            },
         },
     }
     // check that proposal status: stake withdrawn?
@@ -677,87 +1033,372 @@ pub fn try_retrieve_prop_stake<S: Storage, A: Api, Q:Querier>(
     Ok(HandleResponse {
         messages: vec![],
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::VoteReservationPrice { status: Success })?),
+        data: Some(to_binary(&HandleAnswer::RetrievePropStake { status: Success })?),
     })
 }
 
-/// For ftoken holders to claim their pro-rata share of sale proceeds, after a bid has won 
-pub fn try_claim_proceeds<S: Storage, A: Api, Q:Querier>(
+/// For proposers to cancel their proposal and retrieve their stake. Allowed before any votes
+/// are cast on the proposal, or within the cancellation period after the proposal is made
+pub fn try_cancel_proposal<S: Storage, A: Api, Q:Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
+    prop_id: u32,
 ) -> StdResult<HandleResponse> {
-    // check that vault has closed
-    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
-    if let true = ftkn_info.vault_active {
-        return Err(StdError::generic_err("vault is still active"))
+    let mut prop_info = props_r(&deps.storage).load(&prop_id.to_le_bytes())?;
+    if prop_info.proposer != env.message.sender {
+        return Err(StdError::generic_err("only the proposer can cancel a proposal"))
+    }
+    if prop_info.outcome.is_some() {
+        return Err(StdError::generic_err("proposal has already been finalized"))
+    }
+
+    // check cancellation cut-off. Proposals left pending when the vault closed can always be 
+    // cancelled. Under a secret ballot, this check reveals whether the proposal has any votes, 
+    // which is no more than the turnout that proposal queries already show
+    let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
+    let vault_active = ftoken_info_r(&deps.storage).load()?.vault_active;
+    let votes_total = votes_total_r(&deps.storage).load(&prop_id.to_le_bytes())?;
+    let cancel_height = prop_info.snapshot_height.saturating_add(ftkn_conf.prop_conf.cancel_period);
+    if vault_active && env.block.height > cancel_height && votes_total.total() > Uint128(0) {
+        return Err(StdError::generic_err(format!(
+            "proposal has votes, and the cancellation period ended at height {}", cancel_height
+        )))
+    }
+
+    cancel_prop(deps, &env, &mut prop_info)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: cancel_prop_log(&prop_info),
+        data: Some(to_binary(&HandleAnswer::CancelProposal { status: Success })?),
+    })
+}
+
+/// Guardians set in the ftoken config can change the vault status without a proposal
+pub fn try_set_vault_status<S: Storage, A: Api, Q:Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    status: VaultStatus,
+) -> StdResult<HandleResponse> {
+    let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
+    if !ftkn_conf.guardians.contains(&env.message.sender) {
+        return Err(StdError::generic_err("only guardians can change the vault status"))
     }
+    vault_status_w(&mut deps.storage).save(&status)?;
 
-    // storage: how much ftoken does sender have?
-    let balances = Balances::from_storage(&mut deps.storage);
-    let account_balance = balances.balance(&deps.api.canonical_address(&env.message.sender)?);
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![log("vault_status", format!("{:?}", status))],
+        data: Some(to_binary(&HandleAnswer::SetVaultStatus { status: Success })?),
+    })
+}
+
+/// For addresses that voted `veto` on a vetoed proposal to claim their pro-rata share of 
+/// the slashed proposal stake, if the proposal's slashing destination is `VetoVoters`. 
+/// Shares are by voting power regardless of the vote scheme. Delegates claim with their own 
+/// ftokens, while their delegators claim with the ftokens they delegated
+pub fn try_claim_slashed_stake<S: Storage, A: Api, Q:Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    prop_id: u32,
+) -> StdResult<HandleResponse> {
+    let prop_info = props_r(&deps.storage).load(&prop_id.to_le_bytes())?;
+    if prop_info.outcome != Some(VoteResult::LostWithVeto) || prop_info.slash_dest != SlashDest::VetoVoters {
+        return Err(StdError::generic_err("proposal stake is not distributed to veto voters"))
+    }
+
+    let sender = to_binary(&env.message.sender)?;
+    if slash_claims_r(&deps.storage, prop_id).may_load(sender.as_slice())?.is_some() {
+        return Err(StdError::generic_err("you have already claimed your share of the slashed stake"))
+    }
+    let veto_power = slash_claim_power(&deps.storage, &env.message.sender, &prop_info)?;
+    if veto_power == Uint128(0) {
+        return Err(StdError::generic_err("you did not vote veto on this proposal, directly or through a delegate"))
+    }
+
+    // pro-rata share of the stake that remains unclaimed, by veto voting power that remains unclaimed. 
+    // The last claimant receives the rounding remainder
+    let powers_total = vote_powers_total_r(&deps.storage).load(&prop_id.to_le_bytes())?;
+    let mut claimed = slash_claimed_r(&deps.storage).may_load(&prop_id.to_le_bytes())?.unwrap_or_default();
+    let amount = Uint128(calc_redemption(
+        veto_power.u128(), 
+        powers_total.veto.u128().saturating_sub(claimed.veto.u128()), 
+        prop_info.stake.u128().saturating_sub(claimed.amount.u128()),
+    )?);
+    claimed.veto += veto_power;
+    claimed.amount += amount;
+    slash_claimed_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &claimed)?;
+    slash_claims_w(&mut deps.storage, prop_id).save(sender.as_slice(), &amount)?;
 
-    // transfer ownership of all sender's ftokens to this contract
-    // can change to burn mechanism: might be more robust to division inaccuracies esp for very small ftoken holders
     try_transfer_impl(
         deps, 
-        &deps.api.canonical_address(&env.message.sender.clone())?,
-        &deps.api.canonical_address(&env.contract.address.clone())?,
-        Uint128(account_balance),
+        &deps.api.canonical_address(&env.contract.address)?,
+        &deps.api.canonical_address(&env.message.sender)?,
+        amount,
         None,
         &env.block,
     )?;
 
-    // load winning bid amount
-    let (winning_bid, _) = get_last_bid(&deps.storage)?;
-    let sale_proceeds = winning_bid.amount;
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ClaimSlashedStake { status: Success })?),
+    })
+}
+
+/// For ftoken holders to claim their pro-rata share of sale proceeds, after a bid has won 
+pub fn try_claim_proceeds<S: Storage, A: Api, Q:Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // check that vault has closed
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if let true = ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault is still active"))
+    }
+
+    // sender's ftokens, including those staked, unbonding or in retrievable proposal stakes
+    let claimable = claimable_ftokens(deps, &env.message.sender)?;
+    let account_balance = claimable.total();
+    if account_balance == 0 {
+        return Err(StdError::generic_err("you have no ftokens to claim proceeds with"))
+    }
 
-    // calculate amount of bid (in SNIP20 tokens) to transfer to sender     
-    let config = Config::from_storage(&mut deps.storage);
-    let total_supply = config.total_supply();
+    let messages = claim_proceeds_impl(deps, &env, &env.message.sender, claimable)?;
 
-    let pro_rata_proceeds = calc_pro_rata(account_balance, total_supply, sale_proceeds.u128())?;
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ClaimProceeds { status: Success })?),
+    })
+}
 
-    // can delete
-    // // u128::MAX has 38 zeros. Even in the most extreme case, this shouldn't cause precision errors
-    // // let precision = U256::from(10u8).pow(U256::from(39u8));
-    // let precision = U256::MAX;
+/// Pays sale proceeds, and shares of leftover treasury balances, to a holder for their claimable 
+/// ftokens, which are burned. Returns the SNIP20 transfer messages
+fn claim_proceeds_impl<S: Storage, A: Api, Q:Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    claimant: &HumanAddr,
+    claimable: ClaimableFtokens,
+) -> StdResult<Vec<CosmosMsg>> {
+    let account_balance = claimable.total();
+
+    // load sale proceeds, from the winning bid or an accepted buyout offer
+    let mut sale_proceeds = sale_proceeds_r(&deps.storage).load()?;
+
+    // calculate amount of bid (in SNIP20 tokens) to transfer to claimant, as the claimant's share of the 
+    // proceeds that remain in the pool. Ftokens held by the treasury do not receive a share of proceeds
+    let mut treasury = treasury_r(&deps.storage).load()?;
+    let remaining_supply = proceeds_ftoken_supply(&deps.storage, &treasury)?;
+    let remaining_proceeds = sale_proceeds.amount.u128().saturating_sub(sale_proceeds.claimed.u128());
+
+    let pro_rata_proceeds = calc_redemption(account_balance, remaining_supply, remaining_proceeds)?;
+
+    // burn the claimant's ftokens. Ftokens held by this contract on behalf of the claimant are burned 
+    // from the contract's balance
+    if claimable.balance > 0 {
+        try_burn_impl(
+            &mut deps.storage, 
+            &deps.api.canonical_address(claimant)?, 
+            Uint128(claimable.balance), 
+            Some("claimed sale proceeds".to_string()), 
+            &env.block,
+        )?;
+    }
+    let held = account_balance - claimable.balance;
+    if held > 0 {
+        try_burn_impl(
+            &mut deps.storage, 
+            &deps.api.canonical_address(&env.contract.address)?, 
+            Uint128(held), 
+            Some(format!("claimed sale proceeds for {}", claimant)), 
+            &env.block,
+        )?;
+    }
+    let claimant_bin = to_binary(claimant)?;
+    let claimant_u8 = claimant_bin.as_slice();
+    if let Some(mut ftkn_stake) = ftkn_stake_r(&deps.storage).may_load(claimant_u8)? {
+        ftkn_stake.amount = Uint128(0);
+        ftkn_stake_w(&mut deps.storage).save(claimant_u8, &ftkn_stake)?;
+    }
+    unbonding_w(&mut deps.storage).remove(claimant_u8);
+    for prop_id in claimable.prop_ids {
+        let mut prop_info = props_r(&deps.storage).load(&prop_id.to_le_bytes())?;
+        prop_info.cancel_if_vault_closed(false);
+        prop_info.stake_withdrawn = true;
+        props_w(&mut deps.storage).save(&prop_id.to_le_bytes(), &prop_info)?;
+    }
 
-    // let tot_supply_u256 = U256::from(total_supply);
-    // let sale_proceeds_u256 = U256::from(sale_proceeds.u128());
-    // let acc_bal_u256 = U256::from(account_balance);
-    // let acc_bal_u256_pres = acc_bal_u256.saturating_mul(precision);
-    
-    // // pro-rata proportion, in approx exp(39) precision
-    // let pro_rata_percent_op = (acc_bal_u256_pres).checked_div(tot_supply_u256); 
-    // let pro_rata_percent = match pro_rata_percent_op {
-    //     None => return Err(StdError::generic_err("Total ftoken supply is zero...")),
-    //     Some(i) => i,
-    // };
-
-    // // Note if sale_proceeds is u128::MAX, this still should not overflow as 2^256 = 2^128^2, and
-    // // pro_rata_percent < u128::MAX. But note that U256::MAX != (u128::MAX)^2, although close, perhaps 
-    // // due to the way it is implemented?
-    // let pro_rata_proceeds = pro_rata_percent.saturating_mul(sale_proceeds_u256).checked_div(precision).unwrap().low_u128();
-    // let pro_rata_proceeds = Uint128(pro_rata_proceeds);
+    // record the claim
+    sale_proceeds.claimed_ftokens += Uint128(account_balance);
+    sale_proceeds.claimed += Uint128(pro_rata_proceeds);
+    sale_proceeds_w(&mut deps.storage).save(&sale_proceeds)?;
+    let mut claim = proceeds_claims_r(&deps.storage).may_load(claimant_u8)?.unwrap_or_default();
+    claim.ftokens += Uint128(account_balance);
+    claim.proceeds += Uint128(pro_rata_proceeds);
+    proceeds_claims_w(&mut deps.storage).save(claimant_u8, &claim)?;
 
     // create `Transfer` msg to send to SNIP20 ("sSCRT") contract, to transfer pro-rata proceeds to ftoken holder
-    let ftoken_config = ftkn_config_r(&deps.storage).load()?;
     let message = snip20_transfer_msg(
-        env.message.sender, 
+        claimant.clone(), 
         Uint128(pro_rata_proceeds), 
-        ftoken_config.auc_conf.bid_token.address, 
-        ftoken_config.auc_conf.bid_token.code_hash
+        sale_proceeds.token.address, 
+        sale_proceeds.token.code_hash
     )?;
 
-    let messages = vec![message];
+    let mut messages = vec![message];
+
+    // pro-rata share of SNIP20 tokens left over in the treasury
+    for snip20 in treasury.snip20s.iter_mut() {
+        let share = calc_redemption(account_balance, remaining_supply, snip20.balance.u128())?;
+        if share == 0 {
+            continue
+        }
+        snip20.balance = Uint128(snip20.balance.u128() - share);
+        snip20.distributed += Uint128(share);
+        messages.push(snip20_transfer_msg(
+            claimant.clone(), 
+            Uint128(share), 
+            snip20.token.address.clone(), 
+            snip20.token.code_hash.clone(),
+        )?);
+    }
+    treasury_w(&mut deps.storage).save(&treasury)?;
+
+    Ok(messages)
+}
+
+/// Pushes sale proceeds to ftoken holders, walking the holder index from where the last call left 
+/// off. Up to `limit` holders are processed per call. Holders with nothing to claim, including 
+/// those that have already claimed, are skipped. Anyone can call this
+pub fn try_distribute_proceeds<S: Storage, A: Api, Q:Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    limit: u32,
+) -> StdResult<HandleResponse> {
+    // check that vault has closed
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault is still active"))
+    }
+
+    // ftokens held by this contract are claimed by the holders it holds them for
+    let contract = deps.api.canonical_address(&env.contract.address)?;
+    let mut cursor = proceeds_cursor_r(&deps.storage).may_load()?.unwrap_or(0);
+    let mut messages = vec![];
+    for _ in 0..limit {
+        let holder = match may_get_holder(&deps.storage, cursor)? {
+            Some(i) => i,
+            None => break,
+        };
+        cursor += 1;
+        if holder == contract {
+            continue
+        }
+        let holder = deps.api.human_address(&holder)?;
+        let claimable = claimable_ftokens(deps, &holder)?;
+        if claimable.total() == 0 {
+            continue
+        }
+        messages.extend(claim_proceeds_impl(deps, &env, &holder, claimable)?);
+    }
+    proceeds_cursor_w(&mut deps.storage).save(&cursor)?;
 
     Ok(HandleResponse {
         messages,
         log: vec![],
-        data: Some(to_binary(&HandleAnswer::ClaimProceeds { status: Success })?),
+        data: Some(to_binary(&HandleAnswer::DistributeProceeds { status: Success })?),
     })
 }
 
+/// ftokens that an address can claim sale proceeds with
+pub(crate) struct ClaimableFtokens {
+    /// ftokens held by the address
+    pub balance: u128,
+    /// staked and unbonding ftokens
+    pub staked: u128,
+    /// stakes of the address' proposals that can be retrieved
+    pub prop_stakes: u128,
+    pub prop_ids: Vec<u32>,
+}
+
+impl ClaimableFtokens {
+    pub fn total(&self) -> u128 {
+        self.balance + self.staked + self.prop_stakes
+    }
+}
+
+pub(crate) fn claimable_ftokens<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    addr: &HumanAddr,
+) -> StdResult<ClaimableFtokens> {
+    let addr_bin = to_binary(addr)?;
+    let balance = ReadonlyBalances::from_storage(&deps.storage).account_amount(&deps.api.canonical_address(addr)?);
+    let staked = ftkn_stake_r(&deps.storage).may_load(addr_bin.as_slice())?.unwrap_or_default().amount.u128()
+        + unbonding_r(&deps.storage).may_load(addr_bin.as_slice())?.unwrap_or_default()
+            .iter().map(|entry| entry.amount.u128()).sum::<u128>();
+
+    // includes the stake of proposals cancelled when the vault closed, which is not refunded yet
+    let vault_active = ftoken_info_r(&deps.storage).load()?.vault_active;
+    let mut prop_stakes = 0u128;
+    let mut prop_ids = vec![];
+    for prop_id in get_proposer_props(&deps.storage, addr)? {
+        let mut prop_info = props_r(&deps.storage).load(&prop_id.to_le_bytes())?;
+        prop_info.cancel_if_vault_closed(vault_active);
+        if !prop_info.stake_withdrawn && matches!(
+            prop_info.outcome, 
+            Some(VoteResult::Won) | Some(VoteResult::Lost) | Some(VoteResult::Cancelled)
+        ) {
+            prop_stakes += prop_info.stake.u128();
+            prop_ids.push(prop_id);
+        }
+    }
+
+    Ok(ClaimableFtokens { balance, staked, prop_stakes, prop_ids })
+}
+
+/// ftoken supply that has a claim on the remaining sale proceeds: ftokens not held by the treasury, 
+/// and not orphaned in this contract. Ftokens burned to claim proceeds no longer count towards this. 
+/// Slashed stakes that veto voters have not claimed still count, as they can be claimed at any time
+pub(crate) fn proceeds_ftoken_supply<S: Storage>(
+    storage: &S,
+    treasury: &Treasury,
+) -> StdResult<u128> {
+    let orphaned = orphaned_ftkn_r(storage).may_load()?.unwrap_or_default();
+    Ok(ReadonlyConfig::from_storage(storage).total_supply()
+        .saturating_sub(treasury.ftoken.u128())
+        .saturating_sub(orphaned.u128()))
+}
+
+/// Records ftokens transferred or sent to this contract directly, rather than through ftoken 
+/// functionality such as staking. No address can retrieve these, so they do not have a claim on 
+/// the sale proceeds
+pub fn record_orphaned_ftokens<S: Storage>(
+    storage: &mut S,
+    recipient: &HumanAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    if recipient != &ReadonlyConfig::from_storage(storage).constants()?.contract_address {
+        return Ok(())
+    }
+    let orphaned = orphaned_ftkn_r(storage).may_load()?.unwrap_or_default();
+    orphaned_ftkn_w(storage).save(&(orphaned + amount))
+}
+
+/// Share of a pool paid out for burning `burned` out of the `remaining_supply` ftokens that have a 
+/// claim on it. Redeeming against what remains, rather than the original pool and supply, carries 
+/// rounding dust forward to later claimants, and the last claimant sweeps whatever is left
+pub(crate) fn calc_redemption(
+    burned: u128,
+    remaining_supply: u128,
+    pool: u128,
+) -> StdResult<u128> {
+    if burned >= remaining_supply {
+        return Ok(pool);
+    }
+    calc_pro_rata(burned, remaining_supply, pool)
+}
+
 /// Implements voting a reservation price for the underlying NFT 
 /// This tx increases bonded period of staked ftokens
 pub fn try_vote_resv_price<S: Storage, A: Api, Q: Querier>(
@@ -765,19 +1406,30 @@ pub fn try_vote_resv_price<S: Storage, A: Api, Q: Querier>(
     env: Env,
     resv_price: Uint128,
 ) -> StdResult<HandleResponse> {
-    // load existing aggregate reservation price
-    let curr_agg = agg_resv_price_r(&deps.storage).load()?;
+    check_vault_status(&deps.storage, VaultOp::Auction)?;
+    expire_resv_votes(&mut deps.storage, env.block.height, MAX_LAZY_RESV_EXPIRIES)?;
+
+    // load existing reservation price
+    let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
+    let curr_price = curr_resv_price(&deps.storage, &ftkn_conf.auc_conf.resv_agg)?;
     
     // check that input reservation price is within bounds
-    let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
-    let floor = curr_agg.uint128_price().multiply_ratio(Uint128(100), ftkn_conf.auc_conf.resv_boundary);
-    let ceiling = curr_agg.uint128_price().multiply_ratio(ftkn_conf.auc_conf.resv_boundary, Uint128(100));
+    let floor = curr_price.multiply_ratio(Uint128(100), ftkn_conf.auc_conf.resv_boundary);
+    let ceiling = curr_price.multiply_ratio(ftkn_conf.auc_conf.resv_boundary, Uint128(100));
     if resv_price > ceiling || resv_price < floor {
         return Err(StdError::generic_err(
             format!("Reserve price out of bounds. Please set between {} and {}", floor, ceiling)
         ));
     }
 
+    // delegators cannot vote directly on the reservation price
+    let delegation = delegs_r(&deps.storage).may_load(to_binary(&env.message.sender)?.as_slice())?.unwrap_or_default();
+    if let Some(delegate) = delegation.resv_price {
+        return Err(StdError::generic_err(format!(
+            "reservation price vote has been delegated to {}", delegate
+        )));
+    }
+
     // load user's existing reservation price vote
     let curr_usr_resv_op = resv_price_r(&deps.storage).may_load(&to_binary(&env.message.sender)?.as_slice())?;
     let curr_usr_resv = match curr_usr_resv_op {
@@ -790,17 +1442,19 @@ pub fn try_vote_resv_price<S: Storage, A: Api, Q: Querier>(
     let sender_u8 = sender.as_slice();
     let mut ftkn_stake = ftkn_stake_r(&deps.storage).load(sender_u8)?;
 
-    // save reservation price
-    let new_usr_resv = ResvVote::new(ftkn_stake.amount, resv_price);
+    // save reservation price, weighted by own and delegated ftokens
+    let deleg_power = deleg_power_r(&deps.storage).may_load(sender_u8)?.unwrap_or_default();
+    let new_usr_resv = ResvVote::new(ftkn_stake.amount + deleg_power.resv_price, resv_price);
     resv_price_w(&mut deps.storage).save(sender_u8, &new_usr_resv)?;
+    resv_vote_heights_w(&mut deps.storage).save(sender_u8, &env.block.height)?;
+    push_resv_vote_entry(&mut deps.storage, &ResvVoteEntry { 
+        voter: env.message.sender.clone(), 
+        height: env.block.height, 
+    })?;
 
     // calculate and save new aggregate reservation price
-    let new_agg_resv = new_agg_resv_vote(
-        &curr_agg, 
-        &curr_usr_resv, 
-        &new_usr_resv
-    );
-    agg_resv_price_w(&mut deps.storage).save(&new_agg_resv)?;
+    update_agg_resv(&mut deps.storage, &curr_usr_resv, &new_usr_resv)?;
+    checkpoint_resv_price(&mut deps.storage, env.block.height)?;
 
     // update staked ftoken bonded period to max of i) current unlock height, ii) min bond period 
     ftkn_stake.unlock_height = ftkn_stake.unlock_height.max(
@@ -816,6 +1470,147 @@ pub fn try_vote_resv_price<S: Storage, A: Api, Q: Querier>(
 }
 
 
+/// Removes up to `limit` expired reservation price votes from the reservation price. Anyone can 
+/// call this
+pub fn try_expire_resv_votes<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A ,Q>,
+    env: Env,
+    limit: u32,
+) -> StdResult<HandleResponse> {
+    expire_resv_votes(&mut deps.storage, env.block.height, limit)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::ExpireResvVotes { status: Success })?),
+    })
+}
+
+/// Delegates voting weight of staked ftokens for proposals and/or reservation price votes.
+/// Delegated weight for proposals counts when the delegate (re)votes on a proposal. Delegated
+/// weight for reservation price changes the delegate's existing reservation price vote immediately
+pub fn try_delegate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    proposals: Option<HumanAddr>,
+    resv_price: Option<HumanAddr>,
+) -> StdResult<HandleResponse> {
+    check_vault_status(&deps.storage, VaultOp::Governance)?;
+
+    let sender = env.message.sender.clone();
+    if proposals.as_ref() == Some(&sender) || resv_price.as_ref() == Some(&sender) {
+        return Err(StdError::generic_err("cannot delegate to yourself"))
+    }
+
+    // check if ftokens are still bonded
+    let sender_bin = to_binary(&sender)?;
+    let sender_u8 = sender_bin.as_slice();
+    let mut ftkn_stake = ftkn_stake_r(&deps.storage).may_load(sender_u8)?.unwrap_or_default();
+    if env.block.height < ftkn_stake.unlock_height {
+        return Err(StdError::generic_err(format!("ftokens are still bonded. Will unbond at height {}", ftkn_stake.unlock_height)))
+    }
+
+    let old_deleg = delegs_r(&deps.storage).may_load(sender_u8)?.unwrap_or_default();
+
+    // proposal delegation
+    if old_deleg.proposals != proposals {
+        if let Some(old_delegate) = &old_deleg.proposals {
+            let mut power = deleg_power_r(&deps.storage).load(to_binary(old_delegate)?.as_slice())?;
+            if env.block.height < power.unlock_height {
+                return Err(StdError::generic_err(format!(
+                    "delegated ftokens are still bonded. Will unbond at height {}", power.unlock_height
+                )))
+            }
+            power.proposals = power.proposals.sub(ftkn_stake.amount)?;
+            deleg_power_w(&mut deps.storage).save(to_binary(old_delegate)?.as_slice(), &power)?;
+        }
+        if let Some(new_delegate) = &proposals {
+            let mut power = deleg_power_r(&deps.storage).may_load(to_binary(new_delegate)?.as_slice())?.unwrap_or_default();
+            power.proposals += ftkn_stake.amount;
+            deleg_power_w(&mut deps.storage).save(to_binary(new_delegate)?.as_slice(), &power)?;
+        }
+    }
+
+    // reservation price delegation
+    if old_deleg.resv_price != resv_price {
+        if let Some(old_delegate) = &old_deleg.resv_price {
+            let mut power = deleg_power_r(&deps.storage).load(to_binary(old_delegate)?.as_slice())?;
+            power.resv_price = power.resv_price.sub(ftkn_stake.amount)?;
+            deleg_power_w(&mut deps.storage).save(to_binary(old_delegate)?.as_slice(), &power)?;
+            reweigh_resv_vote(&mut deps.storage, old_delegate)?;
+        }
+        if let Some(new_delegate) = &resv_price {
+            // sender's own reservation price vote is replaced by its delegate's vote
+            if let Some(old_resv) = resv_price_r(&deps.storage).may_load(sender_u8)? {
+                update_agg_resv(&mut deps.storage, &old_resv, &ResvVote::default())?;
+                resv_price_w(&mut deps.storage).remove(sender_u8);
+                resv_vote_heights_w(&mut deps.storage).remove(sender_u8);
+            }
+            let mut power = deleg_power_r(&deps.storage).may_load(to_binary(new_delegate)?.as_slice())?.unwrap_or_default();
+            power.resv_price += ftkn_stake.amount;
+            deleg_power_w(&mut deps.storage).save(to_binary(new_delegate)?.as_slice(), &power)?;
+            reweigh_resv_vote(&mut deps.storage, new_delegate)?;
+        }
+    }
+
+    delegs_w(&mut deps.storage).save(sender_u8, &Delegation { proposals: proposals.clone(), resv_price })?;
+
+    // changing delegations bonds staked ftokens, same as voting
+    let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
+    ftkn_stake.unlock_height = ftkn_stake.unlock_height.max(
+        env.block.height.checked_add(ftkn_conf.min_ftkn_bond_prd).unwrap()
+    );
+    ftkn_stake_w(&mut deps.storage).save(sender_u8, &ftkn_stake)?;
+
+    // save voting power checkpoints
+    checkpoint_power(&mut deps.storage, &sender, env.block.height)?;
+    for delegate in old_deleg.proposals.iter().chain(proposals.iter()) {
+        checkpoint_power(&mut deps.storage, delegate, env.block.height)?;
+        reweigh_prop_votes(&mut deps.storage, delegate, env.block.height)?;
+    }
+    checkpoint_resv_price(&mut deps.storage, env.block.height)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Delegate { status: Success })?),
+    })
+}
+
+
+/// Deposits ftokens into the vault treasury. Treasury ftokens can only be paid out
+/// through a `TreasurySpend` proposal
+pub fn try_treasury_deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Uint128,
+) -> StdResult<HandleResponse> {
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if !ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault no longer active"))
+    };
+    check_vault_status(&deps.storage, VaultOp::Other)?;
+
+    try_transfer_impl(
+        deps, 
+        &deps.api.canonical_address(&env.message.sender)?,
+        &deps.api.canonical_address(&env.contract.address)?,
+        amount,
+        None,
+        &env.block,
+    )?;
+
+    let mut treasury = treasury_r(&deps.storage).load()?;
+    treasury.ftoken += amount;
+    treasury_w(&mut deps.storage).save(&treasury)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::TreasuryDeposit { status: Success })?),
+    })
+}
+
 
 /////////////////////////////////////////////////////////////////////////////////
 // Callback receiver functions
@@ -898,37 +1693,330 @@ pub fn try_batch_receive_nft<S: Storage, A: Api, Q: Querier>(
 }
 
 
-// /// SNIP20 sends back Snip20ReceiveMsg message. This function is called after:
-// /// * (none)
-// pub fn try_receive_snip20<S: Storage, A: Api, Q: Querier>(
-//     deps: &mut Extern<S, A, Q>,
-//     env: Env,
-//     snip20receivemsg: Snip20ReceiveMsg,   
-// ) -> StdResult<HandleResponse> {
-//     // load ftkn_conf
-//     let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
+/// SNIP20 sends back Snip20ReceiveMsg message when a SNIP20 token is sent to this contract.
+/// Tokens registered with the vault treasury are added to the treasury balance. Other tokens
+/// are rejected, so the SNIP20 `Send` fails and the tokens are not lost
+pub fn try_receive_snip20<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    snip20receivemsg: Snip20ReceiveMsg,   
+) -> StdResult<HandleResponse> {
+    // treasury funds are distributed with sale proceeds once the vault closes
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if !ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault no longer active"))
+    };
+    check_vault_status(&deps.storage, VaultOp::Other)?;
 
-//     // security check: triggered by this contract (ie: the Receive{sender} account is this contract)
-//     if snip20receivemsg.sender != env.contract.address {
-//         return Err(StdError::generic_err(
-//             "`HandleMsg::Receive` is not a public interface. The `sender` of the receive message must be this contract itself"
-//         ));
-//     }
-//     // security check: comes from allowed (eg: sSCRT) token contract
-//     if env.message.sender != ftkn_conf.auc_conf.bid_token.address { //<--- note, not auc_conf snapshot
-//         return Err(StdError::generic_err(
-//             "Enter token contract address of an allowed bid token"
-//         ));
-//     }
-    
-//     Ok(HandleResponse::default())
-// }
+    // security check: comes from a token contract registered with the treasury
+    let mut treasury = treasury_r(&deps.storage).load()?;
+    let snip20 = match treasury.snip20_mut(&env.message.sender) {
+        Some(i) => i,
+        None => return Err(StdError::generic_err("token is not registered with the vault treasury")),
+    };
+    snip20.balance += snip20receivemsg.amount;
+    treasury_w(&mut deps.storage).save(&treasury)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Receive { status: Success })?),
+    })
+}
+
+/// Offer to buy up to `amount` ftokens at `price` bid tokens per ftoken, open for `period` blocks.
+/// The full offer amount is escrowed in bid tokens
+pub fn try_make_tender_offer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Uint128,
+    price: Uint128,
+    period: u64,
+) -> StdResult<HandleResponse> {
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if !ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault no longer active"))
+    };
+    check_vault_status(&deps.storage, VaultOp::Auction)?;
+
+    if amount == Uint128(0) || price == Uint128(0) || period == 0 {
+        return Err(StdError::generic_err("tender offer amount, price and period must be greater than zero"))
+    }
+    let escrow = amount.u128().checked_mul(price.u128())
+        .ok_or_else(|| StdError::generic_err("tender offer amount is too large"))?;
+
+    let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
+    let offer_id = tender_offer_id_r(&deps.storage).load()?;
+    let offer = TenderOffer {
+        offer_id,
+        offeror: env.message.sender.clone(),
+        token: ftkn_conf.auc_conf.bid_token,
+        price,
+        max_amount: amount,
+        tendered: Uint128(0),
+        end_height: env.block.height.saturating_add(period),
+        settled: false,
+    };
+    tender_offers_w(&mut deps.storage).save(&offer_id.to_le_bytes(), &offer)?;
+    tender_offer_id_w(&mut deps.storage).save(&offer_id.add(1u32))?;
+
+    // escrow bid tokens for the full offer
+    let message = snip20_transferfrom_msg(
+        env.message.sender,
+        env.contract.address,
+        Uint128(escrow),
+        offer.token.address,
+        offer.token.code_hash,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![message],
+        log: vec![log("offer_id", offer_id)],
+        data: Some(to_binary(&HandleAnswer::MakeTenderOffer { status: Success })?),
+    })
+}
+
+/// Tender ftokens to an open tender offer. The sender is paid from the offer's escrow immediately, 
+/// and the ftokens are delivered to the offeror when the offer is settled
+pub fn try_tender<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    offer_id: u32,
+    amount: Uint128,
+) -> StdResult<HandleResponse> {
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if !ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault no longer active"))
+    };
+    check_vault_status(&deps.storage, VaultOp::Auction)?;
+
+    let mut offer = match tender_offers_r(&deps.storage).may_load(&offer_id.to_le_bytes())? {
+        Some(i) => i,
+        None => return Err(StdError::generic_err("offer id refers to a non-existent tender offer")),
+    };
+    if env.block.height > offer.end_height {
+        return Err(StdError::generic_err("tender offer has expired"))
+    }
+    if amount == Uint128(0) {
+        return Err(StdError::generic_err("cannot tender zero ftokens"))
+    }
+    if amount > offer.remaining() {
+        return Err(StdError::generic_err(format!(
+            "tender offer can only accept {} more ftokens", offer.remaining()
+        )))
+    }
+
+    // ftokens are held by the contract until the offer is settled
+    try_transfer_impl(
+        deps, 
+        &deps.api.canonical_address(&env.message.sender)?,
+        &deps.api.canonical_address(&env.contract.address)?,
+        amount,
+        None,
+        &env.block,
+    )?;
+    offer.tendered += amount;
+    tender_offers_w(&mut deps.storage).save(&offer_id.to_le_bytes(), &offer)?;
+
+    let message = snip20_transfer_msg(
+        env.message.sender,
+        Uint128(offer.price.u128() * amount.u128()),
+        offer.token.address,
+        offer.token.code_hash,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![message],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::Tender { status: Success })?),
+    })
+}
+
+/// Anyone can settle a tender offer once it has expired or is fully tendered. Tendered ftokens are
+/// delivered to the offeror, and unspent escrow is refunded to the offeror
+pub fn try_settle_tender_offer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    offer_id: u32,
+) -> StdResult<HandleResponse> {
+    let mut offer = match tender_offers_r(&deps.storage).may_load(&offer_id.to_le_bytes())? {
+        Some(i) => i,
+        None => return Err(StdError::generic_err("offer id refers to a non-existent tender offer")),
+    };
+    if offer.settled {
+        return Err(StdError::generic_err("tender offer has already been settled"))
+    }
+    if env.block.height <= offer.end_height && offer.remaining() > Uint128(0) {
+        return Err(StdError::generic_err(format!(
+            "tender offer is open until height {}", offer.end_height
+        )))
+    }
+    offer.settled = true;
+    tender_offers_w(&mut deps.storage).save(&offer_id.to_le_bytes(), &offer)?;
+
+    // deliver tendered ftokens
+    if offer.tendered > Uint128(0) {
+        try_transfer_impl(
+            deps, 
+            &deps.api.canonical_address(&env.contract.address)?,
+            &deps.api.canonical_address(&offer.offeror)?,
+            offer.tendered,
+            None,
+            &env.block,
+        )?;
+    }
+
+    // refund unspent escrow
+    let mut messages = vec![];
+    let refund = Uint128(offer.price.u128() * offer.remaining().u128());
+    if refund > Uint128(0) {
+        messages.push(snip20_transfer_msg(
+            offer.offeror,
+            refund,
+            offer.token.address,
+            offer.token.code_hash,
+        )?);
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::SettleTenderOffer { status: Success })?),
+    })
+}
+
+
+/// Offer to buy the underlying NFT for `amount` bid tokens. The offer is escrowed and binding for 
+/// `period` blocks, during which ftoken holders can accept it through an `AcceptOffer` proposal
+pub fn try_make_buyout_offer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Uint128,
+    period: u64,
+) -> StdResult<HandleResponse> {
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if !ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault no longer active"))
+    };
+    check_vault_status(&deps.storage, VaultOp::Auction)?;
+
+    if amount == Uint128(0) {
+        return Err(StdError::generic_err("buyout offer amount must be greater than zero"))
+    }
+
+    let ftkn_conf = ftkn_config_r(&deps.storage).load()?;
+    let offer_id = buyout_offer_id_r(&deps.storage).load()?;
+    let offer = BuyoutOffer {
+        offer_id,
+        buyer: env.message.sender.clone(),
+        token: ftkn_conf.auc_conf.bid_token,
+        amount,
+        end_height: env.block.height.saturating_add(period),
+        prop_id: None,
+        accepted: false,
+        withdrawn: false,
+    };
+    buyout_offers_w(&mut deps.storage).save(&offer_id.to_le_bytes(), &offer)?;
+    buyout_offer_id_w(&mut deps.storage).save(&offer_id.add(1u32))?;
+
+    // escrow offer amount
+    let message = snip20_transferfrom_msg(
+        env.message.sender,
+        env.contract.address,
+        amount,
+        offer.token.address,
+        offer.token.code_hash,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![message],
+        log: vec![log("offer_id", offer_id)],
+        data: Some(to_binary(&HandleAnswer::MakeBuyoutOffer { status: Success })?),
+    })
+}
+
+/// Buyers can withdraw an offer that was not accepted once it expires, once the vault closes, or 
+/// once a proposal to accept it is rejected or cancelled
+pub fn try_withdraw_buyout_offer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    offer_id: u32,
+) -> StdResult<HandleResponse> {
+    let mut offer = load_buyout_offer(&deps.storage, offer_id)?;
+    if offer.buyer != env.message.sender {
+        return Err(StdError::generic_err("only the buyer can withdraw a buyout offer"))
+    }
+    if offer.accepted {
+        return Err(StdError::generic_err("buyout offer has been accepted"))
+    }
+    if offer.withdrawn {
+        return Err(StdError::generic_err("buyout offer has already been withdrawn"))
+    }
+
+    let vault_active = ftoken_info_r(&deps.storage).load()?.vault_active;
+    let prop_outcome = match offer.prop_id {
+        Some(prop_id) => {
+            let mut prop_info = props_r(&deps.storage).load(&prop_id.to_le_bytes())?;
+            prop_info.cancel_if_vault_closed(vault_active);
+            Some(prop_info.outcome)
+        },
+        None => None,
+    };
+    if let Some(None) = prop_outcome {
+        return Err(StdError::generic_err("proposal to accept this buyout offer has not been finalized"))
+    }
+    if env.block.height <= offer.end_height && vault_active && prop_outcome.is_none() {
+        return Err(StdError::generic_err(format!(
+            "buyout offer is binding until height {}", offer.end_height
+        )))
+    }
+
+    offer.withdrawn = true;
+    buyout_offers_w(&mut deps.storage).save(&offer_id.to_le_bytes(), &offer)?;
+
+    let message = snip20_transfer_msg(
+        offer.buyer,
+        offer.amount,
+        offer.token.address,
+        offer.token.code_hash,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![message],
+        log: vec![],
+        data: Some(to_binary(&HandleAnswer::WithdrawBuyoutOffer { status: Success })?),
+    })
+}
 
 
 /////////////////////////////////////////////////////////////////////////////////
 // Private functions
 /////////////////////////////////////////////////////////////////////////////////
 
+/// function to generate `RegisterReceive` cosmos_msg to send to SNIP20 token contract
+/// # Arguments
+/// * `code_hash` - the code hash of this contract, which receives the `Receive` callbacks
+/// * `contract_addr` - the address of the SNIP20 contract
+/// * `callback_code_hash` - the code hash of the SNIP20 contract
+fn snip20_register_receive_msg(
+    code_hash: String,
+    contract_addr: HumanAddr,
+    callback_code_hash: String,
+) -> StdResult<CosmosMsg> {
+    let message = to_binary(&InterContrMsg::RegisterReceive{
+        code_hash,
+        padding: None,
+    })?;
+    let cosmos_message = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr,
+        callback_code_hash,
+        msg: message,
+        send: vec![],
+    });
+    
+    Ok(cosmos_message)
+}
+
 /// function to generate `Transfer` cosmos_msg to send to SNIP20 token contract
 /// # Arguments
 /// * `recipient` - token transfer to this address
@@ -1097,72 +2185,158 @@ fn snip20_transferfrom_msg(
 //     Ok(HandleResponse::default())
 // }
 
-/// determines result of vote
-fn determine_vote_res<S: Storage>(
-    // deps: &mut Extern<S, A, Q>,
-    storage: &mut S,
-    prop_id: u32,
-    quorum: Uint128,
-    veto_threshold: Uint128,
-) -> StdResult<VoteResult> {
-    // final vote tally
-    let vote_tally = votes_total_r(storage).load(&prop_id.to_le_bytes())?;
-
-    // load configs
-    let config = ReadonlyConfig::from_storage(storage);
+/// vault operations, grouped by the vault status that pauses them
+enum VaultOp {
+    /// paused by `StopAuction` and `StopAll`
+    Auction,
+    /// paused by `StopGovernance` and `StopAll`
+    Governance,
+    /// paused by `StopAll` only
+    Other,
+}
 
-    // if vote >= veto threshold -> LostWithVeto
-    let veto_proportion = (vote_tally.veto)
-        .multiply_ratio(Uint128(10_000), Uint128(config.total_supply()));
-    if veto_proportion >= veto_threshold {
-        return Ok(VoteResult::LostWithVeto)
+/// returns an error if the vault status pauses the operation. Exits are never checked, so they
+/// stay open regardless of the vault status
+fn check_vault_status<S: Storage>(storage: &S, op: VaultOp) -> StdResult<()> {
+    let status = vault_status_r(storage).load()?;
+    let paused = matches!(
+        (&status, op),
+        (VaultStatus::StopAll, _)
+        | (VaultStatus::StopAuction, VaultOp::Auction)
+        | (VaultStatus::StopGovernance, VaultOp::Governance)
+    );
+    if paused {
+        return Err(StdError::generic_err(format!("operation paused. Vault status is {:?}", status)))
     }
+    Ok(())
+}
 
-    // if `yes` + `no` + `veto` + `abstain` < quorum -> Lost
-    let vote_proportion = (vote_tally.yes + vote_tally.no + vote_tally.veto + vote_tally.abstain)
-        .multiply_ratio(Uint128(10_000), Uint128(config.total_supply()));
-    if vote_proportion < quorum {
-        return Ok(VoteResult::Lost)
+/// proposals to change the vault status stay open when governance is paused, so the DAO can 
+/// always resume the vault
+fn check_prop_vault_status<S: Storage>(storage: &S, proposal: &Proposal) -> StdResult<()> {
+    match proposal {
+        Proposal::SetVaultStatus { .. } => Ok(()),
+        _ => check_vault_status(storage, VaultOp::Governance),
+    }
+}
+
+/// checks that a string field does not exceed `max_len` characters
+fn check_len(field: &str, value: &str, max_len: usize) -> StdResult<()> {
+    if value.chars().count() > max_len {
+        return Err(StdError::generic_err(format!(
+            "proposal {} cannot exceed {} characters", field, max_len
+        )))
+    }
+    Ok(())
+}
+
+/// determines result of vote, given the vote thresholds of the proposal's type. The veto threshold 
+/// and quorum are proportions of the total supply, so are measured with voting power. The vote 
+/// scheme only weights `yes` against `no` for the pass threshold
+fn determine_vote_res<S: Storage>(
+    // deps: &mut Extern<S, A, Q>,
+    storage: &mut S,
+    prop_id: u32,
+    thresholds: &VoteThresholds,
+) -> StdResult<VoteResult> {
+    // final vote tally
+    let vote_tally = votes_total_r(storage).load(&prop_id.to_le_bytes())?;
+    let power_tally = vote_powers_total_r(storage).may_load(&prop_id.to_le_bytes())?.unwrap_or_default();
+    let total_supply = Uint128(ReadonlyConfig::from_storage(storage).total_supply());
+
+    // if vote >= veto threshold -> LostWithVeto
+    let veto_proportion = (power_tally.veto)
+        .multiply_ratio(Uint128(10_000), total_supply);
+    if veto_proportion >= thresholds.veto_threshold {
+        return Ok(VoteResult::LostWithVeto)
     }
 
-    // if no >= yes -> Lost 
-    if vote_tally.no >= vote_tally.yes {
+    // if `yes` + `no` + `veto` + `abstain` < quorum -> Lost
+    let vote_proportion = power_tally.total()
+        .multiply_ratio(Uint128(10_000), total_supply);
+    if vote_proportion < thresholds.vote_quorum {
         return Ok(VoteResult::Lost)
     }
 
-    // if yes > no -> Won
-    if vote_tally.yes > vote_tally.no {
+    // if yes / (yes + no) > pass threshold -> Won, else Lost
+    let yes_bp = U256::from(vote_tally.yes.u128()) * U256::from(10_000u128);
+    let pass_bp = U256::from(thresholds.pass_threshold.u128()) * U256::from((vote_tally.yes + vote_tally.no).u128());
+    if yes_bp > pass_bp {
         return Ok(VoteResult::Won)
     }
 
-    // else, something strange happened.... should not be reachable
-    return Err(StdError::generic_err("unable to determine vote result"))
+    Ok(VoteResult::Lost)
+}
+
+/// vote thresholds that apply to a proposal. Proposals to send messages to the underlying NFT
+/// use the thresholds of the message's policy
+fn prop_thresholds(prop_conf: &PropConf, proposal: &Proposal) -> VoteThresholds {
+    match proposal {
+        Proposal::MsgToNft { msg } => prop_conf.nft_msg_thresholds(&msg.msg_type()),
+        _ => prop_conf.thresholds(&proposal.prop_type()),
+    }
 }
 
-/// private function: execute proposal if won
+/// true if the voting power voting `yes` on a proposal reaches the emergency threshold, as a 
+/// proportion of the total supply. The vote scheme does not apply, so conviction that has not been 
+/// accrued yet does not count. A threshold of 0 disables early execution
+fn emergency_threshold_met<S: Storage>(
+    storage: &S,
+    prop_id: u32,
+    emergency_threshold: Uint128,
+) -> StdResult<bool> {
+    let power_tally = vote_powers_total_r(storage).may_load(&prop_id.to_le_bytes())?.unwrap_or_default();
+    let yes_proportion = power_tally.yes
+        .multiply_ratio(Uint128(10_000), ReadonlyConfig::from_storage(storage).total_supply());
+    Ok(yes_proportion >= emergency_threshold)
+}
+
+/// private function: execute proposal if won. Returns messages to be sent, or `None` if the 
+/// proposal can no longer be executed
 fn try_execute_proposal<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     proposal: Proposal,
-) -> StdResult<()> {
-    match proposal {
+) -> StdResult<Option<Vec<CosmosMsg>>> {
+    let resp = match proposal {
         Proposal::MsgToNft { msg } => {
             try_send_msg_to_nft(
-                &deps.storage,
+                &mut deps.storage,
                 msg,
-            )?;
+            )?
         },
         Proposal::ChangeConfig { config } => {
-            try_change_config(&mut deps.storage, config)?;
+            try_change_config(&mut deps.storage, env.block.height, config)?
         },
-    }
+        Proposal::RegisterTreasuryToken { token } => {
+            try_register_treasury_token(&mut deps.storage, env, token)?
+        },
+        Proposal::TreasurySpend { token, payouts } => {
+            try_treasury_spend(deps, env, token, payouts)?
+        },
+        Proposal::RotateViewingKey {  } => {
+            try_rotate_nft_vk(deps, env)?
+        },
+        Proposal::RevokePermits { permit_names } => {
+            try_revoke_nft_permits(&deps.storage, permit_names)?
+        },
+        Proposal::SetVaultStatus { status } => {
+            try_change_vault_status(&mut deps.storage, status)?
+        },
+        Proposal::AcceptOffer { offer_id } => {
+            match try_accept_offer(deps, env, offer_id)? {
+                Some(resp) => resp,
+                None => return Ok(None),
+            }
+        },
+    };
 
-    return Ok(())
+    Ok(Some(resp.messages))
 }
 
 /// sends message to underlying NFT
 fn try_send_msg_to_nft<S: Storage>(
-    storage: &S,
+    storage: &mut S,
     msg: AllowedNftMsg,   
 ) -> StdResult<HandleResponse> {
     let ftkn_info = ftoken_info_r(storage).load()?;
@@ -1201,16 +2375,153 @@ fn try_send_msg_to_nft<S: Storage>(
             expires, 
             padding: None,
         },
+        AllowedNftMsg::SetRoyaltyInfo { royalty_info } => S721HandleMsg::SetRoyaltyInfo { 
+            token_id: Some(token_id), 
+            royalty_info, 
+            padding: None,
+        },
+        AllowedNftMsg::Revoke { spender } => S721HandleMsg::Revoke { 
+            spender, 
+            token_id, 
+            padding: None,
+        },
+        AllowedNftMsg::RevokeAll { operator } => S721HandleMsg::RevokeAll { operator, padding: None },
+        AllowedNftMsg::RevokePermit { permit_name } => S721HandleMsg::RevokePermit { permit_name, padding: None },
     };
 
-    // create cosmos_msg binary
-    let cosmos_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+    Ok(HandleResponse {
+        messages: vec![nft_cosmos_msg(storage, &message)?],
+        log: vec![],
+        data: None,
+    })
+}
+
+/// replaces the vault's viewing key on the underlying NFT with a newly generated key
+fn try_rotate_nft_vk<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<HandleResponse> {
+    // old key used as additional entropy
+    let prng_seed = ReadonlyConfig::from_storage(&deps.storage).constants()?.prng_seed;
+    let old_vk = nft_vk_r(&deps.storage).load()?;
+    let vk = ViewingKey::new(&env, &prng_seed, old_vk.to_string().as_bytes());
+    nft_vk_w(&mut deps.storage).save(&vk)?;
+
+    let message = S721HandleMsg::SetViewingKey { key: vk.to_string(), padding: None };
+    Ok(HandleResponse {
+        messages: vec![nft_cosmos_msg(&deps.storage, &message)?],
+        log: vec![],
+        data: None,
+    })
+}
+
+/// revokes query permits of the vault on the underlying NFT
+fn try_revoke_nft_permits<S: Storage>(
+    storage: &S,
+    permit_names: Vec<String>,
+) -> StdResult<HandleResponse> {
+    let mut messages = vec![];
+    for permit_name in permit_names {
+        let message = S721HandleMsg::RevokePermit { permit_name, padding: None };
+        messages.push(nft_cosmos_msg(storage, &message)?);
+    }
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: None,
+    })
+}
+
+/// creates cosmos_msg to be sent to the underlying NFT contract
+fn nft_cosmos_msg<S: Storage>(
+    storage: &S,
+    message: &S721HandleMsg,
+) -> StdResult<CosmosMsg> {
+    let ftkn_info = ftoken_info_r(storage).load()?;
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: ftkn_info.instance.init_nft_info.nft_contr.address,
         callback_code_hash: ftkn_info.instance.init_nft_info.nft_contr.code_hash,
-        msg: to_binary(&message)?,
+        msg: to_binary(message)?,
         send: vec![],
-    });
-    
+    }))
+}
+
+/// slashes the stake of a vetoed proposal, according to the proposal's slashing destination.
+/// Stakes distributed to veto voters are claimed by each voter
+fn slash_prop_stake<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    prop_info: &mut PropInfo,
+) -> StdResult<()> {
+    match prop_info.slash_dest {
+        SlashDest::Burn => {
+            try_burn_impl(
+                &mut deps.storage, 
+                &deps.api.canonical_address(&env.contract.address)?, 
+                prop_info.stake, 
+                Some(format!("slashed stake of proposal {}", prop_info.prop_id)), 
+                &env.block,
+            )?;
+        },
+        SlashDest::Treasury => {
+            let mut treasury = treasury_r(&deps.storage).load()?;
+            treasury.ftoken += prop_info.stake;
+            treasury_w(&mut deps.storage).save(&treasury)?;
+        },
+        SlashDest::VetoVoters => return Ok(()),
+    }
+    prop_info.stake_withdrawn = true;
+
+    Ok(())
+}
+
+/// cancels a proposal that has not been finalized, and refunds the stake to the proposer
+fn cancel_prop<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    prop_info: &mut PropInfo,
+) -> StdResult<()> {
+    try_transfer_impl(
+        deps, 
+        &deps.api.canonical_address(&env.contract.address)?,
+        &deps.api.canonical_address(&prop_info.proposer)?,
+        prop_info.stake,
+        None,
+        &env.block,
+    )?;
+
+    prop_info.outcome = Some(VoteResult::Cancelled);
+    prop_info.stake_withdrawn = true;
+    props_w(&mut deps.storage).save(&prop_info.prop_id.to_le_bytes(), prop_info)
+}
+
+/// log of a cancelled proposal and the stake refunded to its proposer
+fn cancel_prop_log(prop_info: &PropInfo) -> Vec<LogAttribute> {
+    vec![
+        log("cancelled_prop_id", prop_info.prop_id),
+        log("refunded_stake", prop_info.stake),
+    ]
+}
+
+/// registers a SNIP20 token with the vault treasury
+fn try_register_treasury_token<S: Storage>(
+    storage: &mut S,
+    env: Env,
+    token: ContractInfo,
+) -> StdResult<HandleResponse> {
+    let mut treasury = treasury_r(storage).load()?;
+    if treasury.snip20_mut(&token.address).is_some() {
+        return Err(StdError::generic_err("token is already registered with the vault treasury"))
+    }
+
+    let cosmos_msg = snip20_register_receive_msg(
+        env.contract_code_hash,
+        token.address.clone(),
+        token.code_hash.clone(),
+    )?;
+    treasury.snip20s.push(TreasuryBalance { token, balance: Uint128(0), distributed: Uint128(0) });
+    treasury_w(storage).save(&treasury)?;
+
     Ok(HandleResponse {
         messages: vec![cosmos_msg],
         log: vec![],
@@ -1218,16 +2529,248 @@ fn try_send_msg_to_nft<S: Storage>(
     })
 }
 
+/// pays out vault treasury funds
+fn try_treasury_spend<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    token: Option<HumanAddr>,
+    payouts: Vec<Payout>,
+) -> StdResult<HandleResponse> {
+    // treasury funds are distributed with sale proceeds once the vault closes
+    let ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if !ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault no longer active"))
+    };
+
+    let mut treasury = treasury_r(&deps.storage).load()?;
+    let total = payouts.iter().map(|i| i.amount.u128()).sum::<u128>();
+    let balance = treasury_spend_balance(&treasury, &token)?;
+    if total > balance.u128() {
+        return Err(StdError::generic_err(format!(
+            "insufficient treasury balance: {} available", balance
+        )))
+    }
+
+    let mut messages = vec![];
+    match token {
+        None => {
+            treasury.ftoken = Uint128(treasury.ftoken.u128() - total);
+            for payout in payouts {
+                try_transfer_impl(
+                    deps, 
+                    &deps.api.canonical_address(&env.contract.address)?,
+                    &deps.api.canonical_address(&payout.recipient)?,
+                    payout.amount,
+                    None,
+                    &env.block,
+                )?;
+            }
+        },
+        Some(address) => {
+            let snip20 = treasury.snip20_mut(&address).unwrap();
+            snip20.balance = Uint128(snip20.balance.u128() - total);
+            for payout in payouts {
+                messages.push(snip20_transfer_msg(
+                    payout.recipient, 
+                    payout.amount, 
+                    snip20.token.address.clone(), 
+                    snip20.token.code_hash.clone(),
+                )?);
+            }
+        },
+    }
+    treasury_w(&mut deps.storage).save(&treasury)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![],
+        data: None,
+    })
+}
+
+/// treasury balance of a registered SNIP20 token, or of ftokens if `token` is `None`
+fn treasury_spend_balance(
+    treasury: &Treasury,
+    token: &Option<HumanAddr>,
+) -> StdResult<Uint128> {
+    match token {
+        None => Ok(treasury.ftoken),
+        Some(address) => match treasury.snip20s.iter().find(|i| &i.token.address == address) {
+            Some(snip20) => Ok(snip20.balance),
+            None => Err(StdError::generic_err("token is not registered with the vault treasury")),
+        },
+    }
+}
+
 /// private function: changes ftoken contract config
 /// note that config does not change the config of a live auction
 fn try_change_config<S: Storage>(
     storage: &mut S,
+    height: u64,
     config: FtokenConf,
 ) -> StdResult<HandleResponse> {
     ftkn_config_w(storage).save(&config)?;
+    // changing the aggregation method can change the reservation price
+    checkpoint_resv_price(storage, height)?;
     Ok(HandleResponse::default())
 }
 
+/// sells the underlying NFT to the buyer of an accepted buyout offer, and closes the vault. The 
+/// escrowed offer amount becomes the sale proceeds. Returns `None` if the offer has expired
+fn try_accept_offer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    offer_id: u32,
+) -> StdResult<Option<HandleResponse>> {
+    let mut ftkn_info = ftoken_info_r(&deps.storage).load()?;
+    if !ftkn_info.vault_active {
+        return Err(StdError::generic_err("vault no longer active"))
+    }
+    let mut offer = load_buyout_offer(&deps.storage, offer_id)?;
+    if offer.withdrawn {
+        return Err(StdError::generic_err("buyout offer has been withdrawn"))
+    }
+
+    // the proposal may be finalized after the offer stops being binding. The offer is then not 
+    // accepted, and the buyer can withdraw it
+    if offer.end_height < env.block.height {
+        return Ok(None)
+    }
+
+    let send_nft_msg = send_nft_msg(
+        deps, 
+        env.clone(), 
+        ftkn_info.instance.init_nft_info.nft_contr.address.clone(), 
+        ftkn_info.instance.init_nft_info.nft_contr.code_hash.clone(), 
+        offer.buyer.clone(), 
+        ftkn_info.instance.init_nft_info.token_id.clone(), 
+        None,
+    )?;
+
+    offer.accepted = true;
+    buyout_offers_w(&mut deps.storage).save(&offer_id.to_le_bytes(), &offer)?;
+    sale_proceeds_w(&mut deps.storage).save(&SaleProceeds {
+        token: offer.token,
+        amount: offer.amount,
+        claimed_ftokens: Uint128(0),
+        claimed: Uint128(0),
+    })?;
+
+    // close vault: save state. Pending proposals are treated as cancelled from here on
+    ftkn_info.vault_active = false;
+    ftoken_info_w(&mut deps.storage).save(&ftkn_info)?;
+
+    Ok(Some(HandleResponse {
+        messages: vec![send_nft_msg],
+        log: vec![],
+        data: None,
+    }))
+}
+
+fn load_buyout_offer<S: Storage>(storage: &S, offer_id: u32) -> StdResult<BuyoutOffer> {
+    match buyout_offers_r(storage).may_load(&offer_id.to_le_bytes())? {
+        Some(i) => Ok(i),
+        None => Err(StdError::generic_err("offer id refers to a non-existent buyout offer")),
+    }
+}
+
+fn try_change_vault_status<S: Storage>(
+    storage: &mut S,
+    status: VaultStatus,
+) -> StdResult<HandleResponse> {
+    vault_status_w(storage).save(&status)?;
+    Ok(HandleResponse::default())
+}
+
+/// updates the aggregate reservation price vote and the reservation price median, when an address' 
+/// reservation price vote changes from `old` to `new`
+fn update_agg_resv<S: Storage>(
+    storage: &mut S,
+    old: &ResvVote,
+    new: &ResvVote,
+) -> StdResult<()> {
+    let curr_agg = agg_resv_price_r(storage).load()?;
+    agg_resv_price_w(storage).save(&new_agg_resv_vote(&curr_agg, old, new))?;
+
+    if old.uint128_stake() > Uint128(0) {
+        remove_resv_stake(storage, old.uint128_price(), old.uint128_stake())?;
+    }
+    if new.uint128_stake() > Uint128(0) {
+        add_resv_stake(storage, new.uint128_price(), new.uint128_stake())?;
+    }
+    Ok(())
+}
+
+/// walks the reservation price vote queue from the cursor, removing votes that have expired, and 
+/// stops at the first live vote or after `limit` queued votes. Queued votes that have since been 
+/// recast or removed are skipped. Returns true if all expired votes have been removed
+fn expire_resv_votes<S: Storage>(
+    storage: &mut S,
+    height: u64,
+    limit: u32,
+) -> StdResult<bool> {
+    let lifetime = ftkn_config_r(storage).load()?.auc_conf.resv_vote_lifetime;
+    if lifetime == 0 {
+        return Ok(true)
+    }
+
+    let mut cursor = resv_vote_cursor_r(storage).load()?;
+    let mut checked = 0u32;
+    let cleared = loop {
+        let entry = match may_get_resv_vote_entry(storage, cursor)? {
+            Some(i) if i.height.saturating_add(lifetime) <= height => i,
+            _ => break true,
+        };
+        if checked == limit {
+            break false
+        }
+        let voter = to_binary(&entry.voter)?;
+        if resv_vote_heights_r(storage).may_load(voter.as_slice())? == Some(entry.height) {
+            if let Some(old_resv) = resv_price_r(storage).may_load(voter.as_slice())? {
+                update_agg_resv(storage, &old_resv, &ResvVote::default())?;
+                resv_price_w(storage).remove(voter.as_slice());
+            }
+            resv_vote_heights_w(storage).remove(voter.as_slice());
+        }
+        cursor += 1;
+        checked += 1;
+    };
+
+    if checked > 0 {
+        resv_vote_cursor_w(storage).save(&cursor)?;
+        checkpoint_resv_price(storage, height)?;
+    }
+    Ok(cleared)
+}
+
+/// saves a checkpoint of the reservation price and the stake that voted on it, if either has changed
+fn checkpoint_resv_price<S: Storage>(
+    storage: &mut S,
+    height: u64,
+) -> StdResult<()> {
+    let resv_agg = ftkn_config_r(storage).load()?.auc_conf.resv_agg;
+    let checkpoint = ResvCheckpoint {
+        height,
+        price: curr_resv_price(storage, &resv_agg)?,
+        stake: agg_resv_price_r(storage).load()?.uint128_stake(),
+    };
+    push_resv_checkpoint(storage, &checkpoint)
+}
+
+/// reservation price under the aggregation method set in the auction config. If there are no 
+/// votes, the median falls back to the last aggregate reservation price
+pub(crate) fn curr_resv_price<S: Storage>(
+    storage: &S,
+    resv_agg: &ResvAgg,
+) -> StdResult<Uint128> {
+    let agg_resv = agg_resv_price_r(storage).load()?;
+    match resv_agg {
+        ResvAgg::Mean => Ok(agg_resv.uint128_price()),
+        ResvAgg::Median => Ok(resv_median_r(storage).may_load()?.unwrap_or_default().median
+            .unwrap_or_else(|| agg_resv.uint128_price())),
+    }
+}
+
 // function to calculate new aggregate reservation prices -> ResvVote{stake, price}
 fn new_agg_resv_vote(curr_agg: &ResvVote, old: &ResvVote, new: &ResvVote) -> ResvVote {
     let old_agg_stake = U384::from_little_endian(curr_agg.stake.as_slice());
@@ -1251,6 +2794,326 @@ fn new_agg_resv_vote(curr_agg: &ResvVote, old: &ResvVote, new: &ResvVote) -> Res
     )
 }
 
+/// creates a vote register with the full `weight` on the chosen `vote`
+fn vote_register(vote: &Vote, weight: Uint128) -> VoteRegister {
+    let mut vote_reg = VoteRegister::default();
+    match vote {
+        Vote::Yes => vote_reg.yes = weight,
+        Vote::No => vote_reg.no = weight,
+        Vote::Veto => vote_reg.veto = weight,
+        Vote::Abstain => vote_reg.abstain = weight,
+    };
+    vote_reg
+}
+
+/// applies the net effect of replacing the `old` vote with the `new` vote to a vote tally
+fn adj_vote_tally(tally: &mut VoteRegister, new: &VoteRegister, old: &VoteRegister) -> StdResult<()> {
+    tally.yes = (tally.yes + new.yes).sub(old.yes)?;
+    tally.no = (tally.no + new.no).sub(old.no)?;
+    tally.veto = (tally.veto + new.veto).sub(old.veto)?;
+    tally.abstain = (tally.abstain + new.abstain).sub(old.abstain)?;
+    Ok(())
+}
+
+/// height until which a staker's ftokens are bonded because its proposal delegate has
+/// used them to vote
+fn deleg_unlock_height<S: Storage>(storage: &S, delegator: &HumanAddr) -> StdResult<u64> {
+    let delegation = delegs_r(storage).may_load(to_binary(delegator)?.as_slice())?.unwrap_or_default();
+    let unlock_height = match delegation.proposals {
+        Some(delegate) => deleg_power_r(storage).may_load(to_binary(&delegate)?.as_slice())?
+            .unwrap_or_default().unlock_height,
+        None => 0u64,
+    };
+    Ok(unlock_height)
+}
+
+/// Current weight of an address' vote on a proposal: voting power at the proposal's snapshot height,
+/// capped by current voting power. Also returns the delegated weight counted in this vote
+fn prop_vote_weight<S: Storage>(
+    storage: &S,
+    addr: &HumanAddr,
+    prop_info: &PropInfo,
+) -> StdResult<(Uint128, PropDelegTally)> {
+    let addr_bin = to_binary(addr)?;
+    let snapshot = get_power_at(storage, addr, prop_info.snapshot_height)?;
+    let staked = ftkn_stake_r(storage).may_load(addr_bin.as_slice())?.unwrap_or_default().amount;
+    let delegated = deleg_power_r(storage).may_load(addr_bin.as_slice())?.unwrap_or_default().proposals;
+
+    // delegated weight excludes delegators that voted directly
+    let mut deleg_tally = prop_deleg_r(storage, prop_info.prop_id).may_load(addr_bin.as_slice())?.unwrap_or_default();
+    deleg_tally.counted = Uint128(
+        snapshot.delegated.u128().min(delegated.u128()).saturating_sub(deleg_tally.overridden.u128())
+    );
+    let weight = Uint128(snapshot.staked.u128().min(staked.u128())) + deleg_tally.counted;
+
+    Ok((weight, deleg_tally))
+}
+
+/// voting power that an address can claim a share of a slashed proposal stake with: its own 
+/// ftokens in its `veto` vote, or if it did not vote, its delegated ftokens in its delegate's 
+/// `veto` vote. Delegated ftokens counted in the delegate's vote are shared among its delegators
+/// pro rata to what they delegated at the snapshot height
+fn slash_claim_power<S: Storage>(
+    storage: &S,
+    addr: &HumanAddr,
+    prop_info: &PropInfo,
+) -> StdResult<Uint128> {
+    let prop_id = prop_info.prop_id;
+    let addr_bin = to_binary(addr)?;
+    if let Some(power_reg) = vote_powers_r(storage, prop_id).may_load(addr_bin.as_slice())? {
+        let deleg_tally = prop_deleg_r(storage, prop_id).may_load(addr_bin.as_slice())?.unwrap_or_default();
+        return Ok(Uint128(power_reg.veto.u128().saturating_sub(deleg_tally.counted.u128())))
+    }
+
+    let snapshot = get_power_at(storage, addr, prop_info.snapshot_height)?;
+    let delegate = match snapshot.delegate {
+        Some(i) => i,
+        None => return Ok(Uint128(0)),
+    };
+    let delegate_bin = to_binary(&delegate)?;
+    let delegate_veto = vote_powers_r(storage, prop_id).may_load(delegate_bin.as_slice())?.unwrap_or_default().veto;
+    if delegate_veto == Uint128(0) {
+        return Ok(Uint128(0))
+    }
+    let deleg_tally = prop_deleg_r(storage, prop_id).may_load(delegate_bin.as_slice())?.unwrap_or_default();
+    let delegated = get_power_at(storage, &delegate, prop_info.snapshot_height)?.delegated.u128()
+        .saturating_sub(deleg_tally.overridden.u128());
+    if delegated == 0 {
+        return Ok(Uint128(0))
+    }
+    let share = U256::from(snapshot.staked.u128())
+        .saturating_mul(U256::from(deleg_tally.counted.u128()))
+        .checked_div(U256::from(delegated)).unwrap()
+        .low_u128();
+    Ok(Uint128(share))
+}
+
+/// Saves an address' vote on a proposal with its current weight under the proposal's vote scheme, 
+/// and applies the net effect to the proposal's vote tally. `prev_vote` is the address' existing vote
+/// on the proposal, if any. Returns the vote to be indexed
+fn save_prop_vote<S: Storage>(
+    storage: &mut S,
+    addr: &HumanAddr,
+    prop_info: &PropInfo,
+    vote: &Vote,
+    height: u64,
+    prev_vote: Option<&ActiveVote>,
+) -> StdResult<ActiveVote> {
+    let addr_bin = to_binary(addr)?;
+    let addr_u8 = addr_bin.as_slice();
+    let prop_id = prop_info.prop_id;
+    let old_vote_reg = votes_r(storage, prop_id).may_load(addr_u8)?.unwrap_or_default();
+
+    let (power, deleg_tally) = prop_vote_weight(storage, addr, prop_info)?;
+    prop_deleg_w(storage, prop_id).save(addr_u8, &deleg_tally)?;
+    if deleg_tally.counted > Uint128(0) {
+        // delegated ftokens are bonded until end of voting period, same as the voter's own ftokens
+        let mut deleg_power = deleg_power_r(storage).may_load(addr_u8)?.unwrap_or_default();
+        deleg_power.unlock_height = deleg_power.unlock_height.max(prop_info.end_height);
+        deleg_power_w(storage).save(addr_u8, &deleg_power)?;
+    }
+
+    // voting power stays committed from the original vote height, unless the vote changes. Additional
+    // voting power is committed from the current height, so the commitment height becomes the 
+    // power-weighted average
+    let commit_height = match prev_vote {
+        Some(prev) if &prev.vote == vote => {
+            if power > prev.power {
+                let prev_part = prev.power.u128() * prev.height as u128;
+                let new_part = (power.u128() - prev.power.u128()) * height as u128;
+                ((prev_part + new_part) / power.u128()) as u64
+            } else {
+                prev.height
+            }
+        },
+        _ => height,
+    };
+
+    let weight = scheme_weight(prop_info, power, commit_height);
+    let new_vote_reg = vote_register(vote, weight);
+    votes_w(storage, prop_id).save(addr_u8, &new_vote_reg)?;
+
+    let mut votes_total = votes_total_r(storage).load(&prop_id.to_le_bytes())?;
+    adj_vote_tally(&mut votes_total, &new_vote_reg, &old_vote_reg)?;
+    votes_total_w(storage).save(&prop_id.to_le_bytes(), &votes_total)?;
+
+    // voting power tally, for thresholds relative to the total supply
+    let old_power_reg = vote_powers_r(storage, prop_id).may_load(addr_u8)?.unwrap_or_default();
+    let new_power_reg = vote_register(vote, power);
+    vote_powers_w(storage, prop_id).save(addr_u8, &new_power_reg)?;
+    let mut powers_total = vote_powers_total_r(storage).may_load(&prop_id.to_le_bytes())?.unwrap_or_default();
+    adj_vote_tally(&mut powers_total, &new_power_reg, &old_power_reg)?;
+    vote_powers_total_w(storage).save(&prop_id.to_le_bytes(), &powers_total)?;
+
+    Ok(ActiveVote { prop_id, vote: vote.clone(), power, height: commit_height })
+}
+
+/// weight of a vote under the proposal's vote scheme, given the voting power committed to the vote, 
+/// and the height from which it has been committed. Conviction weight is projected to the end of the 
+/// voting period: voting power that is withdrawn or moved to another vote before then is re-weighted, 
+/// so by the time the proposal is finalized, the tally only counts conviction that has been accrued. 
+/// Before then, the tally includes conviction still to be accrued, so early execution of emergency 
+/// proposals does not use it
+fn scheme_weight(
+    prop_info: &PropInfo,
+    power: Uint128,
+    commit_height: u64,
+) -> Uint128 {
+    match prop_info.vote_scheme {
+        VoteScheme::Linear => power,
+        VoteScheme::Quadratic => Uint128(isqrt(power.u128())),
+        VoteScheme::Conviction { max_multiplier } => {
+            let vote_period = prop_info.end_height.saturating_sub(prop_info.snapshot_height);
+            if vote_period == 0 {
+                return power
+            }
+            let committed = prop_info.end_height.saturating_sub(commit_height).min(vote_period);
+            let bonus = power.multiply_ratio(
+                (max_multiplier.saturating_sub(1) as u64 * committed) as u128,
+                vote_period as u128,
+            );
+            power + bonus
+        },
+    }
+}
+
+/// integer square root, rounded down
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n
+    }
+    let mut x = n;
+    let mut y = (x + n / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// loads an address' votes on proposals that are still in their voting period
+fn load_active_votes<S: Storage>(
+    storage: &S,
+    addr: &HumanAddr,
+    height: u64,
+) -> StdResult<Vec<ActiveVote>> {
+    let active_votes = active_votes_r(storage).may_load(to_binary(addr)?.as_slice())?.unwrap_or_default();
+    let mut live_votes = vec![];
+    for active_vote in active_votes {
+        let prop_info = props_r(storage).load(&active_vote.prop_id.to_le_bytes())?;
+        if height <= prop_info.end_height && prop_info.outcome.is_none() {
+            live_votes.push(active_vote);
+        }
+    }
+    Ok(live_votes)
+}
+
+/// re-weights an address' votes on proposals still in their voting period to its current voting power
+fn reweigh_prop_votes<S: Storage>(
+    storage: &mut S,
+    addr: &HumanAddr,
+    height: u64,
+) -> StdResult<()> {
+    let active_votes = load_active_votes(storage, addr, height)?;
+    let mut new_active_votes = vec![];
+    for active_vote in active_votes.iter() {
+        let prop_info = props_r(storage).load(&active_vote.prop_id.to_le_bytes())?;
+        new_active_votes.push(
+            save_prop_vote(storage, addr, &prop_info, &active_vote.vote, height, Some(active_vote))?
+        );
+    }
+    active_votes_w(storage).save(to_binary(addr)?.as_slice(), &new_active_votes)
+}
+
+/// re-weights an address' reservation price vote, if any, to its current voting power: 
+/// own staked ftokens (unless delegated) plus ftokens delegated to it
+fn reweigh_resv_vote<S: Storage>(
+    storage: &mut S,
+    addr: &HumanAddr,
+) -> StdResult<()> {
+    let addr_bin = to_binary(addr)?;
+    let old_resv = match resv_price_r(storage).may_load(addr_bin.as_slice())? {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+    let delegation = delegs_r(storage).may_load(addr_bin.as_slice())?.unwrap_or_default();
+    let own_stake = match delegation.resv_price {
+        Some(_) => Uint128(0),
+        None => ftkn_stake_r(storage).may_load(addr_bin.as_slice())?.unwrap_or_default().amount,
+    };
+    let deleg_power = deleg_power_r(storage).may_load(addr_bin.as_slice())?.unwrap_or_default();
+    let new_resv = ResvVote::new(own_stake + deleg_power.resv_price, old_resv.uint128_price());
+    resv_price_w(storage).save(addr_bin.as_slice(), &new_resv)?;
+
+    update_agg_resv(storage, &old_resv, &new_resv)
+}
+
+/// removes the weight of a delegator that voted directly from its delegate's vote on a proposal
+fn override_delegate_vote<S: Storage>(
+    storage: &mut S,
+    prop_info: &PropInfo,
+    delegate: &HumanAddr,
+    amount: Uint128,
+    height: u64,
+) -> StdResult<()> {
+    let delegate_bin = to_binary(delegate)?;
+    let delegate_u8 = delegate_bin.as_slice();
+    let mut deleg_tally: PropDelegTally = prop_deleg_r(storage, prop_info.prop_id).may_load(delegate_u8)?.unwrap_or_default();
+    deleg_tally.overridden += amount;
+    prop_deleg_w(storage, prop_info.prop_id).save(delegate_u8, &deleg_tally)?;
+
+    // if delegate has already voted, re-weight the delegate's vote without the delegator's weight
+    let mut active_votes = load_active_votes(storage, delegate, height)?;
+    if let Some(idx) = active_votes.iter().position(|active_vote| active_vote.prop_id == prop_info.prop_id) {
+        let prev_vote = active_votes[idx].clone();
+        active_votes[idx] = save_prop_vote(storage, delegate, prop_info, &prev_vote.vote, height, Some(&prev_vote))?;
+        active_votes_w(storage).save(delegate_u8, &active_votes)?;
+    }
+
+    Ok(())
+}
+
+/// adjusts the voting power of a staker's delegates when its staked ftokens change
+fn adj_deleg_power<S: Storage>(
+    storage: &mut S,
+    delegator: &HumanAddr,
+    add: Uint128,
+    sub: Uint128,
+    height: u64,
+) -> StdResult<()> {
+    let delegation = delegs_r(storage).may_load(to_binary(delegator)?.as_slice())?.unwrap_or_default();
+    if let Some(delegate) = delegation.proposals {
+        let mut power: DelegatedPower = deleg_power_r(storage).load(to_binary(&delegate)?.as_slice())?;
+        power.proposals = (power.proposals + add).sub(sub)?;
+        deleg_power_w(storage).save(to_binary(&delegate)?.as_slice(), &power)?;
+        checkpoint_power(storage, &delegate, height)?;
+        reweigh_prop_votes(storage, &delegate, height)?;
+    }
+    if let Some(delegate) = delegation.resv_price {
+        let mut power: DelegatedPower = deleg_power_r(storage).load(to_binary(&delegate)?.as_slice())?;
+        power.resv_price = (power.resv_price + add).sub(sub)?;
+        deleg_power_w(storage).save(to_binary(&delegate)?.as_slice(), &power)?;
+        reweigh_resv_vote(storage, &delegate)?;
+    }
+    Ok(())
+}
+
+/// saves a checkpoint of an address' current voting power on proposals
+fn checkpoint_power<S: Storage>(
+    storage: &mut S,
+    addr: &HumanAddr,
+    height: u64,
+) -> StdResult<()> {
+    let addr_bin = to_binary(addr)?;
+    let checkpoint = PowerCheckpoint {
+        height,
+        staked: ftkn_stake_r(storage).may_load(addr_bin.as_slice())?.unwrap_or_default().amount,
+        delegated: deleg_power_r(storage).may_load(addr_bin.as_slice())?.unwrap_or_default().proposals,
+        delegate: delegs_r(storage).may_load(addr_bin.as_slice())?.unwrap_or_default().proposals,
+    };
+    push_power_checkpoint(storage, addr, &checkpoint)
+}
+
 pub(crate) fn calc_pro_rata(
     num: u128,
     denom: u128,
@@ -1286,8 +3149,13 @@ mod tests {
             state::*,
         }
     };
+    use crate::{
+        contract::{init, handle},
+        msg::{HandleMsg, InitialBalance},
+    };
+    use fsnft_utils::{FtokenContrInit, ThresholdOverride, NftMsgPolicy, NftMsgType, PropType};
     use cosmwasm_std::{
-        // testing::*,
+        testing::*,
         Uint128
     };
 
@@ -1298,6 +3166,70 @@ mod tests {
 
     use super::*;
 
+    fn default_prop_conf() -> PropConf {
+        PropConf {
+            min_stake: Uint128(2),
+            vote_period: 200,
+            vote_quorum: Uint128(2000),
+            veto_threshold: Uint128(1000),
+            pass_threshold: Uint128(5000),
+            emergency_threshold: Uint128(6667),
+            cancel_period: 10,
+            ..PropConf::default()
+        }
+    }
+
+    fn init_helper(
+        initial_balances: &[(&str, u128)],
+        prop_conf: PropConf,
+    ) -> Extern<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies(20, &[]);
+        init(&mut deps, mock_env("instantiator", &[]), init_msg(initial_balances, prop_conf)).unwrap();
+        deps
+    }
+
+    fn init_msg(
+        initial_balances: &[(&str, u128)],
+        prop_conf: PropConf,
+    ) -> InitMsg {
+        let mut init_info = FtokenContrInit::default();
+        init_info.ftkn_conf.prop_conf = prop_conf;
+        InitMsg {
+            init_info,
+            name: "sec-sec".to_string(),
+            admin: Some(HumanAddr("admin".to_string())),
+            symbol: "SECSEC".to_string(),
+            decimals: 8,
+            initial_balances: Some(initial_balances.iter().map(|(address, amount)| InitialBalance {
+                address: HumanAddr(address.to_string()),
+                amount: Uint128(*amount),
+            }).collect()),
+            prng_seed: Binary::from("lolz fun yay".as_bytes()),
+            config: None,
+        }
+    }
+
+    fn handle_at(
+        deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+        sender: &str,
+        height: u64,
+        msg: HandleMsg,
+    ) -> StdResult<HandleResponse> {
+        let mut env = mock_env(sender, &[]);
+        env.block.height = height;
+        handle(deps, env, msg)
+    }
+
+    fn propose_msg(proposal: Proposal) -> HandleMsg {
+        HandleMsg::Propose {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            proposal,
+            stake: Uint128(2),
+        }
+    }
+
     #[test]
     fn new_agg_resv_vote_works() {
         // net effect on reservation price votes
@@ -1385,6 +3317,149 @@ mod tests {
         assert_eq!(res, 1199u128); // rounds down by 1
     }
 
+    #[test]
+    fn invalid_thresholds_are_rejected() {
+        let mut invalid_confs = vec![];
+        for threshold in [0u128, 10_000, 12_000] {
+            invalid_confs.push(PropConf { pass_threshold: Uint128(threshold), ..default_prop_conf() });
+            invalid_confs.push(PropConf { emergency_threshold: Uint128(threshold), ..default_prop_conf() });
+            invalid_confs.push(PropConf {
+                threshold_overrides: vec![ThresholdOverride {
+                    prop_type: PropType::ChangeConfig, vote_quorum: None, pass_threshold: Some(Uint128(threshold)), veto_threshold: None,
+                }],
+                ..default_prop_conf()
+            });
+            invalid_confs.push(PropConf {
+                nft_msg_policies: vec![NftMsgPolicy {
+                    msg_type: NftMsgType::Reveal, vote_quorum: None, pass_threshold: Some(Uint128(threshold)), veto_threshold: None,
+                }],
+                ..default_prop_conf()
+            });
+        }
+
+        let mut deps = init_helper(&[("alice", 100)], default_prop_conf());
+        let mut config = ftkn_config_r(&deps.storage).load().unwrap();
+        for prop_conf in invalid_confs {
+            // at init
+            let mut init_deps = mock_dependencies(20, &[]);
+            let error = init(&mut init_deps, mock_env("instantiator", &[]), init_msg(&[("alice", 100)], prop_conf.clone())).unwrap_err();
+            assert!(error.to_string().contains("must be between 1 and 9999 basis points"));
+
+            // in a proposal to change the config
+            config.prop_conf = prop_conf;
+            let error = handle_at(&mut deps, "alice", 10, propose_msg(Proposal::ChangeConfig { config: config.clone() })).unwrap_err();
+            assert!(error.to_string().contains("must be between 1 and 9999 basis points"));
+        }
+        assert_eq!(prop_id_r(&deps.storage).load().unwrap(), 0);
+
+        // bounds are valid
+        for threshold in [1u128, 9999] {
+            config.prop_conf = PropConf { pass_threshold: Uint128(threshold), emergency_threshold: Uint128(threshold), ..default_prop_conf() };
+            handle_at(&mut deps, "alice", 10, propose_msg(Proposal::ChangeConfig { config: config.clone() })).unwrap();
+        }
+    }
+
+    #[test]
+    fn vote_without_voting_power_fails() {
+        let mut deps = init_helper(&[("alice", 100), ("bob", 100)], default_prop_conf());
+        handle_at(&mut deps, "alice", 10, HandleMsg::Stake { amount: Uint128(50) }).unwrap();
+        let proposal = Proposal::ChangeConfig { config: ftkn_config_r(&deps.storage).load().unwrap() };
+        handle_at(&mut deps, "alice", 11, propose_msg(proposal)).unwrap();
+
+        // bob stakes after the snapshot height, so has no voting power on the proposal
+        handle_at(&mut deps, "bob", 12, HandleMsg::Stake { amount: Uint128(50) }).unwrap();
+        let vote = HandleMsg::VoteProposal { prop_id: 0, vote: Vote::Yes };
+        let error = handle_at(&mut deps, "bob", 13, vote.clone()).unwrap_err();
+        assert!(error.to_string().contains("no voting power on this proposal"));
+        let error = handle_at(&mut deps, "carol", 13, vote).unwrap_err();
+        assert!(error.to_string().contains("no voting power on this proposal"));
+
+        // nothing is saved for failed votes
+        let bob = to_binary(&HumanAddr("bob".to_string())).unwrap();
+        let carol = to_binary(&HumanAddr("carol".to_string())).unwrap();
+        assert_eq!(votes_r(&deps.storage, 0).may_load(bob.as_slice()).unwrap(), None);
+        assert_eq!(votes_r(&deps.storage, 0).may_load(carol.as_slice()).unwrap(), None);
+        assert_eq!(active_votes_r(&deps.storage).may_load(carol.as_slice()).unwrap(), None);
+        assert_eq!(ftkn_stake_r(&deps.storage).may_load(carol.as_slice()).unwrap(), None);
+        assert_eq!(votes_total_r(&deps.storage).load(&0u32.to_le_bytes()).unwrap(), VoteRegister::default());
+    }
+
+    #[test]
+    fn slashed_stake_claims_use_voting_power() {
+        let prop_conf = PropConf {
+            vote_scheme: VoteScheme::Quadratic,
+            slash_dest: SlashDest::VetoVoters,
+            ..default_prop_conf()
+        };
+        let mut deps = init_helper(&[("alice", 100), ("bob", 100), ("carol", 100), ("dave", 100)], prop_conf);
+        handle_at(&mut deps, "alice", 10, HandleMsg::Stake { amount: Uint128(16) }).unwrap();
+        handle_at(&mut deps, "bob", 10, HandleMsg::Stake { amount: Uint128(64) }).unwrap();
+        handle_at(&mut deps, "carol", 10, HandleMsg::Stake { amount: Uint128(20) }).unwrap();
+        let delegate = HandleMsg::Delegate { proposals: Some(HumanAddr("bob".to_string())), resv_price: None };
+        handle_at(&mut deps, "carol", 10, delegate).unwrap();
+        let proposal = Proposal::ChangeConfig { config: ftkn_config_r(&deps.storage).load().unwrap() };
+        let mut propose = propose_msg(proposal);
+        if let HandleMsg::Propose { stake, .. } = &mut propose {
+            *stake = Uint128(10);
+        }
+        handle_at(&mut deps, "dave", 11, propose).unwrap();
+
+        // quadratic vote weights are 4 for alice and sqrt(64 + 20) = 9 for bob, but the stake is 
+        // shared by voting power: 16 for alice, 64 for bob and 20 for bob's delegator carol
+        for voter in ["alice", "bob"] {
+            handle_at(&mut deps, voter, 12, HandleMsg::VoteProposal { prop_id: 0, vote: Vote::Veto }).unwrap();
+        }
+        let votes_total = votes_total_r(&deps.storage).load(&0u32.to_le_bytes()).unwrap();
+        assert_eq!(votes_total.veto, Uint128(4 + 9));
+        handle_at(&mut deps, "dave", 212, HandleMsg::FinalizeExecuteProp { prop_id: 0 }).unwrap();
+        let prop_info = props_r(&deps.storage).load(&0u32.to_le_bytes()).unwrap();
+        assert_eq!(prop_info.outcome, Some(VoteResult::LostWithVeto));
+
+        let balance = |deps: &Extern<MockStorage, MockApi, MockQuerier>, addr: &str| {
+            let canon = deps.api.canonical_address(&HumanAddr(addr.to_string())).unwrap();
+            ReadonlyBalances::from_storage(&deps.storage).account_amount(&canon)
+        };
+        let claim = HandleMsg::ClaimSlashedStake { prop_id: 0 };
+        handle_at(&mut deps, "alice", 213, claim.clone()).unwrap();
+        assert_eq!(balance(&deps, "alice"), 84 + 1);
+        handle_at(&mut deps, "carol", 213, claim.clone()).unwrap();
+        assert_eq!(balance(&deps, "carol"), 80 + 2);
+        // bob claims last with its own 64 ftokens, and receives the rounding remainder
+        handle_at(&mut deps, "bob", 213, claim.clone()).unwrap();
+        assert_eq!(balance(&deps, "bob"), 36 + 7);
+
+        let error = handle_at(&mut deps, "carol", 213, claim.clone()).unwrap_err();
+        assert!(error.to_string().contains("you have already claimed your share of the slashed stake"));
+        let error = handle_at(&mut deps, "dave", 213, claim).unwrap_err();
+        assert!(error.to_string().contains("you did not vote veto on this proposal"));
+    }
+
+
+    #[test]
+    fn expired_offer_is_not_accepted() {
+        let mut deps = init_helper(&[("alice", 100)], default_prop_conf());
+        handle_at(&mut deps, "alice", 10, HandleMsg::Stake { amount: Uint128(50) }).unwrap();
+        let make_offer = HandleMsg::MakeBuyoutOffer { amount: Uint128(1_000), period: 201 };
+        handle_at(&mut deps, "buyer", 10, make_offer).unwrap();
+        handle_at(&mut deps, "alice", 11, propose_msg(Proposal::AcceptOffer { offer_id: 0 })).unwrap();
+        handle_at(&mut deps, "alice", 12, HandleMsg::VoteProposal { prop_id: 0, vote: Vote::Yes }).unwrap();
+
+        // the offer is binding until height 211, but the proposal is finalized after that
+        let handle_resp = handle_at(&mut deps, "alice", 212, HandleMsg::FinalizeExecuteProp { prop_id: 0 }).unwrap();
+        assert!(handle_resp.messages.is_empty());
+        assert_eq!(handle_resp.log, vec![log("not_executed", 0u32)]);
+        let prop_info = props_r(&deps.storage).load(&0u32.to_le_bytes()).unwrap();
+        assert_eq!(prop_info.outcome, Some(VoteResult::Won));
+        assert!(prop_info.has_status(&PropStatus::Passed));
+        assert!(!prop_info.has_status(&PropStatus::Executed));
+
+        // the vault stays open, and the buyer can withdraw the offer
+        assert!(!load_buyout_offer(&deps.storage, 0).unwrap().accepted);
+        assert!(ftoken_info_r(&deps.storage).load().unwrap().vault_active);
+        assert!(sale_proceeds_r(&deps.storage).may_load().unwrap().is_none());
+        handle_at(&mut deps, "buyer", 213, HandleMsg::WithdrawBuyoutOffer { offer_id: 0 }).unwrap();
+    }
+
 
 // Temporary debugging tests
 // -----------------------------------------------------------------------------
@@ -1449,6 +3524,72 @@ mod tests {
         // println!("{}", aad);
     }  
 
+    #[test]
+    fn resv_median_works() {
+        let mut storage = cosmwasm_std::testing::MockStorage::new();
+        let median = |storage: &cosmwasm_std::testing::MockStorage| resv_median_r(storage).load().unwrap();
+
+        add_resv_stake(&mut storage, Uint128(100), Uint128(10)).unwrap();
+        add_resv_stake(&mut storage, Uint128(200), Uint128(30)).unwrap();
+        add_resv_stake(&mut storage, Uint128(150), Uint128(10)).unwrap();
+        assert_eq!(median(&storage), ResvMedian { median: Some(Uint128(200)), below: Uint128(20), above: Uint128(0) });
+
+        remove_resv_stake(&mut storage, Uint128(200), Uint128(30)).unwrap();
+        assert_eq!(median(&storage), ResvMedian { median: Some(Uint128(100)), below: Uint128(0), above: Uint128(10) });
+
+        // new price level is linked between existing levels
+        add_resv_stake(&mut storage, Uint128(120), Uint128(5)).unwrap();
+        assert_eq!(median(&storage), ResvMedian { median: Some(Uint128(120)), below: Uint128(10), above: Uint128(10) });
+        remove_resv_stake(&mut storage, Uint128(100), Uint128(10)).unwrap();
+        assert_eq!(median(&storage), ResvMedian { median: Some(Uint128(150)), below: Uint128(5), above: Uint128(0) });
+
+        remove_resv_stake(&mut storage, Uint128(120), Uint128(5)).unwrap();
+        remove_resv_stake(&mut storage, Uint128(150), Uint128(10)).unwrap();
+        assert_eq!(median(&storage), ResvMedian::default());
+
+        // matches a full scan of votes
+        let mut votes: Vec<(u128, u128)> = vec![];
+        let mut seed = 7u128;
+        for i in 0..200u128 {
+            seed = (seed * 1_103_515_245 + 12_345) % 2_147_483_648;
+            if i % 3 == 2 && !votes.is_empty() {
+                let (price, stake) = votes.remove((seed % votes.len() as u128) as usize);
+                remove_resv_stake(&mut storage, Uint128(price), Uint128(stake)).unwrap();
+            } else {
+                let vote = (seed % 50 + 50, seed % 7 + 1);
+                add_resv_stake(&mut storage, Uint128(vote.0), Uint128(vote.1)).unwrap();
+                votes.push(vote);
+            }
+            let mut sorted = votes.clone();
+            sorted.sort();
+            let total: u128 = sorted.iter().map(|v| v.1).sum();
+            let mut cum = 0u128;
+            let exp = sorted.iter().find(|v| { cum += v.1; cum * 2 >= total }).map(|v| Uint128(v.0));
+            assert_eq!(median(&storage).median, exp);
+        }
+    }
+
+    #[test]
+    fn resv_levels_are_bounded() {
+        let mut storage = cosmwasm_std::testing::MockStorage::new();
+        for price in 1..=MAX_RESV_LEVELS as u128 {
+            add_resv_stake(&mut storage, Uint128(price * 10), Uint128(1)).unwrap();
+        }
+
+        // a vote at a new price fails once the cap is reached, but prices that have votes still 
+        // accept stake
+        let error = add_resv_stake(&mut storage, Uint128(5), Uint128(1)).unwrap_err();
+        assert!(error.to_string().contains("reservation price votes are limited to 200 distinct prices"));
+        add_resv_stake(&mut storage, Uint128(10), Uint128(1)).unwrap();
+
+        // removing the last stake at a price frees its level, including a former median level
+        remove_resv_stake(&mut storage, Uint128(1_000), Uint128(1)).unwrap();
+        add_resv_stake(&mut storage, Uint128(5), Uint128(1)).unwrap();
+        assert_eq!(resv_median_r(&storage).load().unwrap().median, Some(Uint128(990)));
+        let error = add_resv_stake(&mut storage, Uint128(1_005), Uint128(1)).unwrap_err();
+        assert!(error.to_string().contains("reservation price votes are limited to 200 distinct prices"));
+    }
+
     #[test]
     fn test_resvvote_bin_temp() {
         let precision = U384::from(10u128.pow(19));
@@ -7,7 +7,7 @@ use cosmwasm_std::{
 
 use fsnft_utils::{
     FtokenInstance, ContractInfo, UndrNftInfo, FtokenInfo, FtokenConf,
-    AucConf, PropConf,
+    AucConf, PropConf, PropType, VoteThresholds, NftMsgType, ResvAgg,
 };
 use secret_toolkit::{
     // serialization::{Json, Serde}, 
@@ -21,7 +21,7 @@ use crate::{
 };
 
 use super::{
-    state::{StakedTokens, ResvVote, PropInfoTally, VoteRegister, BidInfo},
+    state::{StakedTokens, ResvVote, PropInfoTally, PropStatus, VoteRegister, BidInfo, Delegation, DelegatedPower, PowerCheckpoint, Treasury, VaultStatus, TenderOffer, BuyoutOffer, ResvCheckpoint, UnbondingEntry, Vote, ProceedsClaim},
 };
 
 /////////////////////////////////////////////////////////////////////////////////
@@ -76,6 +76,30 @@ impl InitRes {
 }
 
 
+/////////////////////////////////////////////////////////////////////////////////
+// Receiving ftokens
+/////////////////////////////////////////////////////////////////////////////////
+
+/// Messages that can be attached to a `Send` of ftokens to the ftoken contract itself, so
+/// that ftokens can be used in a single transaction
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FtokenReceiveMsg {
+    /// Stakes the sent ftokens, then optionally votes on a proposal and/or the reservation 
+    /// price with the sender's updated voting power 
+    Stake {
+        proposal_vote: Option<ProposalVote>,
+        resv_price: Option<Uint128>,
+    },
+}
+
+/// Vote on a proposal, made together with staking
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalVote {
+    pub prop_id: u32,
+    pub vote: Vote,
+}
+
 /////////////////////////////////////////////////////////////////////////////////
 // ftoken query messages
 /////////////////////////////////////////////////////////////////////////////////
@@ -95,8 +119,40 @@ pub enum FtokenQuery {
     /// The minimum amount that a bidder needs to bid (to buy out the underlying NFT) in 
     /// order for the bid to be valid.
     ReservationPrice { },
-    /// List of DAO proposals 
-    ProposalList { },
+    /// Reservation price checkpoints, latest first. A checkpoint is saved whenever the reservation
+    /// price or the ftokens that voted on it changes
+    ReservationPriceHistory {
+        page: u32,
+        page_size: u32,
+    },
+    /// Time-weighted average reservation price over blocks `start_height` (inclusive) to 
+    /// `end_height` (exclusive)
+    ReservationPriceTwap {
+        start_height: u64,
+        end_height: u64,
+    },
+    /// List of DAO proposals, latest first. Optionally filtered by status and by proposer. A 
+    /// status filter only scans the latest 500 proposals, or the latest 500 by the proposer
+    ProposalList { 
+        page: u32,
+        page_size: u32,
+        status: Option<PropStatus>,
+        proposer: Option<HumanAddr>,
+    },
+    /// Balances held by the vault treasury
+    TreasuryBalance { },
+    /// Vault status, which determines which vault operations are paused
+    VaultStatus { },
+    /// List of tender offers, latest first
+    TenderOfferList {
+        page: u32,
+        page_size: u32,
+    },
+    /// List of buyout offers, latest first
+    BuyoutOfferList {
+        page: u32,
+        page_size: u32,
+    },
     // Enabling this reduces the privacy of bidders. Blockchain analysis or side chain attacks
     // can easily reveal address of bidders
     BidList { 
@@ -113,9 +169,21 @@ pub enum FtokenAuthQuery {
     NftPrivateMetadata { },
     NftDossier { },
     StakedTokens { },
+    /// Unstaked ftokens that are unbonding, in the order they were unstaked
+    UnbondingTokens { },
     ReservationPriceVote { },
     ProposalVotes { prop_id: u32 },
     Bid { },
+    /// Addresses that the address has delegated its voting weight to
+    Delegation { },
+    /// Staked ftokens plus ftokens delegated to the address, which together determine
+    /// its voting weight
+    VotingPower { },
+    /// Voting power on proposals at a past block height. This is the voting power used for 
+    /// proposals with this snapshot height
+    VotingPowerAt { height: u64 },
+    /// Sale proceeds that the address can claim, and those it has claimed
+    UnclaimedProceeds { },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -132,12 +200,27 @@ pub enum FtokenQueryAnswer {
     },
     ProposalConfig { 
         prop_conf: PropConf,
+        /// vote thresholds that apply to each proposal type, after overrides
+        vote_thresholds: Vec<VoteThresholds>,
     },
     ReservationPrice { 
         ftokens_voted: Uint128,
         reservation_price: Uint128,
+        /// aggregation method of reservation price votes
+        method: ResvAgg,
+    },
+    ReservationPriceHistory {
+        checkpoints: Vec<ResvCheckpoint>,
+        total_checkpoints: u32,
+    },
+    ReservationPriceTwap {
+        twap: Uint128,
     },
     ProposalList(Vec<PropInfoTally>),
+    TreasuryBalance(Treasury),
+    VaultStatus(VaultStatus),
+    TenderOfferList(Vec<TenderOffer>),
+    BuyoutOfferList(Vec<BuyoutOffer>),
     BidList { 
         bid_amounts: Vec<Uint128>,
         total_bids: u64,
@@ -145,9 +228,34 @@ pub enum FtokenQueryAnswer {
     NftPrivateMetadata(PrivateMetadataResponse),
     NftDossier(NftDossierResponse),
     StakedTokens(StakedTokens),
+    UnbondingTokens {
+        entries: Vec<UnbondingEntry>,
+        /// total unbonding ftokens, including entries that have not matured
+        total: Uint128,
+    },
     ReservationPriceVote(ResvVote),
     ProposalVotes(VoteRegister),
     Bid(BidInfo),
+    Delegation(Delegation),
+    VotingPower {
+        /// ftokens staked by the address
+        staked: Uint128,
+        /// ftokens delegated to the address by other stakers
+        delegated: DelegatedPower,
+        /// weight when voting on proposals, before any delegator overrides its vote
+        proposal_power: Uint128,
+        /// weight when voting on the reservation price
+        resv_price_power: Uint128,
+    },
+    VotingPowerAt(PowerCheckpoint),
+    UnclaimedProceeds {
+        /// ftokens that the address can claim proceeds with, including staked and unbonding ftokens, 
+        /// and proposal stakes that can be retrieved
+        ftokens: Uint128,
+        /// proceeds that can be claimed. Zero before the underlying NFT is sold
+        proceeds: Uint128,
+        claimed: ProceedsClaim,
+    },
 }
 
 
@@ -178,12 +286,20 @@ pub enum Snip1155Permissions {
     NftDossier,
     /// For ftokens: Staked ftokens associated with the address
     StakedTokens,
+    /// For ftokens: Unbonding ftokens associated with the address
+    UnbondingTokens,
     /// For ftokens: Reservation price vote by the address
     ReservationPriceVote,
     /// For ftokens: Votes on proposals by the address
     ProposalVotes,
     /// For ftokens: Bids made by the address
     Bid,
+    /// For ftokens: Voting weight delegations made by the address
+    Delegation,
+    /// For ftokens: Voting power of the address, including delegated ftokens
+    VotingPower,
+    /// For ftokens: Sale proceeds that the address can claim and has claimed
+    Proceeds,
 }
 
 pub type Snip1155Permit = Permit<Snip1155Permissions>;
@@ -195,7 +311,9 @@ pub type Snip1155Permit = Permit<Snip1155Permissions>;
 /// List of messages that is allowed to be sent to underlying NFT. ftoken holders
 /// can propose to send these messages to the underlying NFT, where other ftoken 
 /// holders vote on whether to accept the proposal. Once a proposal passes, a  
-/// transaction can be triggered to send the proposed message to the underlying NFT
+/// transaction can be triggered to send the proposed message to the underlying NFT.
+/// Only messages enabled by the proposal config's `nft_msg_policies` can be proposed. 
+/// Transfer approvals cannot be sent while the NFT is fractionalized
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AllowedNftMsg {
@@ -216,6 +334,57 @@ pub enum AllowedNftMsg {
         view_private_metadata: Option<AccessLevel>,
         expires: Option<Expiration>,
     },
+    /// royalties for future sales of the NFT. `None` deletes existing royalty information
+    SetRoyaltyInfo {
+        royalty_info: Option<RoyaltyInfo>,
+    },
+    /// revokes an address' permission to transfer the NFT
+    Revoke {
+        spender: HumanAddr,
+    },
+    /// revokes an operator's permission to transfer all tokens of the vault
+    RevokeAll {
+        operator: HumanAddr,
+    },
+    /// revokes a query permit of the vault
+    RevokePermit {
+        permit_name: String,
+    },
+}
+
+impl AllowedNftMsg {
+    /// message type, which determines whether the message is enabled and the vote thresholds that apply
+    pub fn msg_type(&self) -> NftMsgType {
+        match self {
+            AllowedNftMsg::SetMetadata { .. } => NftMsgType::SetMetadata,
+            AllowedNftMsg::Reveal { } => NftMsgType::Reveal,
+            AllowedNftMsg::MakeOwnershipPrivate { } => NftMsgType::MakeOwnershipPrivate,
+            AllowedNftMsg::SetGlobalApproval { .. } => NftMsgType::SetGlobalApproval,
+            AllowedNftMsg::SetWhitelistedApproval { .. } => NftMsgType::SetWhitelistedApproval,
+            AllowedNftMsg::SetRoyaltyInfo { .. } => NftMsgType::SetRoyaltyInfo,
+            AllowedNftMsg::Revoke { .. } => NftMsgType::Revoke,
+            AllowedNftMsg::RevokeAll { .. } => NftMsgType::RevokeAll,
+            AllowedNftMsg::RevokePermit { .. } => NftMsgType::RevokePermit,
+        }
+    }
+}
+
+/// royalty information of a SNIP721 token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltyInfo {
+    /// decimal places in royalty rates
+    pub decimal_places_in_rates: u8,
+    /// list of royalties
+    pub royalties: Vec<Royalty>,
+}
+
+/// royalty of a SNIP721 token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Royalty {
+    /// address to send royalties to
+    pub recipient: HumanAddr,
+    /// royalty rate
+    pub rate: u16,
 }
 
 /// List of messages that is allowed to be sent to underlying NFT
@@ -279,6 +448,46 @@ pub enum S721HandleMsg {
         /// optional message length padding
         padding: Option<String>,
     },
+    /// set a viewing key for the sender's address
+    SetViewingKey {
+        /// desired viewing key
+        key: String,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// disallow the use of a query permit
+    RevokePermit {
+        /// name of the permit that is no longer valid
+        permit_name: String,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// set royalty information.  If no token ID is provided, this royalty info will become the default
+    /// RoyaltyInfo for any new tokens minted on the contract
+    SetRoyaltyInfo {
+        /// optional id of the token whose royalty information should be updated
+        token_id: Option<String>,
+        /// the new royalty information.  If None, existing royalty information will be deleted
+        royalty_info: Option<RoyaltyInfo>,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// revokes the spender's permission to transfer the specified token
+    Revoke {
+        /// address whose permission is revoked
+        spender: HumanAddr,
+        /// id of the token that the spender can no longer transfer
+        token_id: String,
+        /// optional message length padding
+        padding: Option<String>,
+    },
+    /// revokes the operator's permission to transfer any of the message sender's tokens
+    RevokeAll {
+        /// address whose permissions are revoked
+        operator: HumanAddr,
+        /// optional message length padding
+        padding: Option<String>,
+    },
 }
 
 /// Query messages to be sent to SNIP721 contract. Uses viewing key for cross
@@ -351,4 +560,67 @@ pub enum Proposal {
     ChangeConfig {
         config: FtokenConf,
     },
+    /// Proposal to register a SNIP20 token with the vault treasury, so the ftoken contract
+    /// can receive and hold the token on behalf of ftoken holders
+    RegisterTreasuryToken {
+        token: ContractInfo,
+    },
+    /// Proposal to pay out vault treasury funds
+    TreasurySpend {
+        /// address of a registered SNIP20 token, or `None` to pay out ftokens held by the treasury
+        token: Option<HumanAddr>,
+        payouts: Vec<Payout>,
+    },
+    /// Proposal to replace the vault's viewing key on the underlying NFT with a newly generated key. 
+    /// Can be executed early if `yes` votes reach the emergency threshold
+    RotateViewingKey { },
+    /// Proposal to revoke query permits of the vault on the underlying NFT. Can be executed early if
+    /// `yes` votes reach the emergency threshold
+    RevokePermits {
+        permit_names: Vec<String>,
+    },
+    /// Proposal to change the vault status, which pauses or resumes auction and DAO operations. Can 
+    /// be proposed, voted on and finalized regardless of the vault status, and executed early if `yes` 
+    /// votes reach the emergency threshold
+    SetVaultStatus {
+        status: VaultStatus,
+    },
+    /// Proposal to accept a buyout offer. If the proposal passes, the underlying NFT is sent to the
+    /// buyer, the vault closes, and the escrowed offer amount can be claimed as sale proceeds. The 
+    /// offer must remain binding until the end of the voting period
+    AcceptOffer {
+        offer_id: u32,
+    },
+}
+
+impl Proposal {
+    /// proposal type, which determines the vote thresholds that apply
+    pub fn prop_type(&self) -> PropType {
+        match self {
+            Proposal::MsgToNft { .. } => PropType::MsgToNft,
+            Proposal::ChangeConfig { .. } => PropType::ChangeConfig,
+            Proposal::RegisterTreasuryToken { .. } => PropType::RegisterTreasuryToken,
+            Proposal::TreasurySpend { .. } => PropType::TreasurySpend,
+            Proposal::RotateViewingKey { } => PropType::RotateViewingKey,
+            Proposal::RevokePermits { .. } => PropType::RevokePermits,
+            Proposal::SetVaultStatus { .. } => PropType::SetVaultStatus,
+            Proposal::AcceptOffer { .. } => PropType::AcceptOffer,
+        }
+    }
+
+    /// true if the proposal revokes access to the underlying NFT or changes the vault status, 
+    /// and can be executed early in an emergency
+    pub fn is_emergency(&self) -> bool {
+        matches!(
+            self,
+            Proposal::RotateViewingKey { } | Proposal::RevokePermits { .. } | Proposal::SetVaultStatus { .. }
+        )
+    }
+}
+
+/// Payment from the vault treasury
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payout {
+    pub recipient: HumanAddr,
+    pub amount: Uint128,
 }
@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     to_binary, Storage, Api, Extern,
-    Querier, QueryResult, HumanAddr, StdError, StdResult, 
+    Querier, QueryResult, HumanAddr, StdError, StdResult, Uint128,
 };
 use secret_toolkit::{
     snip721::ViewerInfo, 
@@ -13,17 +13,22 @@ use crate::{
 };
 
 use super::{
-    handles::{calc_pro_rata},
+    handles::{calc_pro_rata, calc_redemption, curr_resv_price, claimable_ftokens, proceeds_ftoken_supply},
     state::{
         ftoken_info_r, nft_vk_r, prop_id_r, props_r, ftkn_config_r, agg_resv_price_r,
         get_bids, ftkn_stake_r, resv_price_r, votes_total_r, 
-        PropInfoTally, votes_r, may_get_bid_from_addr,
+        PropInfoTally, votes_r, may_get_bid_from_addr, delegs_r, deleg_power_r,
+        get_power_at, treasury_r, proposer_props_len, get_proposer_prop, PropStatus, vault_status_r,
+        PropInfo,
+        tender_offers_r, tender_offer_id_r, buyout_offers_r, buyout_offer_id_r,
+        get_resv_checkpoints, get_resv_twap, unbonding_r, sale_proceeds_r, proceeds_claims_r,
     }, 
     msg::{FtokenQuery, FtokenAuthQuery, FtokenQueryAnswer, S721QueryMsg, 
         PrivateMetadataResponse, NftDossierResponse, 
         Snip1155Permit, Snip1155Permissions}, 
     // ft_permit::{Permit, Permission}
 };
+use fsnft_utils::PropType;
 
 
 /////////////////////////////////////////////////////////////////////////////////
@@ -40,7 +45,19 @@ pub fn ftoken_queries<S: Storage, A: Api, Q: Querier>(
         FtokenQuery::AuctionConfig {  } => query_auction_config(&deps.storage),
         FtokenQuery::ProposalConfig {  } => query_proposal_config(&deps.storage),
         FtokenQuery::ReservationPrice {  } => query_reservation_config(&deps.storage),
-        FtokenQuery::ProposalList {  } => query_proposal_list(&deps.storage),
+        FtokenQuery::ReservationPriceHistory { page, page_size } => query_reservation_price_history(
+            &deps.storage, page, page_size,
+        ),
+        FtokenQuery::ReservationPriceTwap { start_height, end_height } => query_reservation_price_twap(
+            &deps.storage, start_height, end_height,
+        ),
+        FtokenQuery::ProposalList { page, page_size, status, proposer } => query_proposal_list(
+            &deps.storage, page, page_size, status, proposer,
+        ),
+        FtokenQuery::TreasuryBalance {  } => query_treasury_balance(&deps.storage),
+        FtokenQuery::VaultStatus {  } => query_vault_status(&deps.storage),
+        FtokenQuery::TenderOfferList { page, page_size } => query_tender_offer_list(&deps.storage, page, page_size),
+        FtokenQuery::BuyoutOfferList { page, page_size } => query_buyout_offer_list(&deps.storage, page, page_size),
         // enabling this reduces the privacy of bidders. Blockchain analysis or side chain attacks
         // can easily reveal address of bidders
         FtokenQuery::BidList { page, page_size } => query_bid_list(&deps.storage, page, page_size),
@@ -85,6 +102,16 @@ pub fn ftoken_permit_queries<S: Storage, A: Api, Q: Querier>(
 
             query_staked_tokens(&deps.storage, account)
         },
+        FtokenAuthQuery::UnbondingTokens {  } => {
+            if !permit.check_permission(&Snip1155Permissions::UnbondingTokens) {
+                return Err(StdError::generic_err(format!(
+                    "No permission to query unbonding tokens, got permissions {:?}",
+                    permit.params.permissions
+                )));
+            }
+
+            query_unbonding_tokens(&deps.storage, account)
+        },
         FtokenAuthQuery::ReservationPriceVote {  } => {
             if !permit.check_permission(&Snip1155Permissions::ReservationPriceVote) {
                 return Err(StdError::generic_err(format!(
@@ -115,6 +142,46 @@ pub fn ftoken_permit_queries<S: Storage, A: Api, Q: Querier>(
 
             query_bid(&deps.storage, account)
         },
+        FtokenAuthQuery::Delegation {  } => {
+            if !permit.check_permission(&Snip1155Permissions::Delegation) {
+                return Err(StdError::generic_err(format!(
+                    "No permission to query delegations, got permissions {:?}",
+                    permit.params.permissions
+                )));
+            }
+
+            query_delegation(&deps.storage, account)
+        },
+        FtokenAuthQuery::VotingPower {  } => {
+            if !permit.check_permission(&Snip1155Permissions::VotingPower) {
+                return Err(StdError::generic_err(format!(
+                    "No permission to query voting power, got permissions {:?}",
+                    permit.params.permissions
+                )));
+            }
+
+            query_voting_power(&deps.storage, account)
+        },
+        FtokenAuthQuery::VotingPowerAt { height } => {
+            if !permit.check_permission(&Snip1155Permissions::VotingPower) {
+                return Err(StdError::generic_err(format!(
+                    "No permission to query voting power, got permissions {:?}",
+                    permit.params.permissions
+                )));
+            }
+
+            query_voting_power_at(&deps.storage, account, height)
+        },
+        FtokenAuthQuery::UnclaimedProceeds {  } => {
+            if !permit.check_permission(&Snip1155Permissions::Proceeds) {
+                return Err(StdError::generic_err(format!(
+                    "No permission to query sale proceeds, got permissions {:?}",
+                    permit.params.permissions
+                )));
+            }
+
+            query_unclaimed_proceeds(deps, account)
+        },
     }
 }
 
@@ -128,9 +195,14 @@ pub fn ftoken_viewing_keys_queries<S: Storage, A: Api, Q: Querier>(
         FtokenAuthQuery::NftPrivateMetadata {  } => query_nft_priv_metadata(&deps, account),
         FtokenAuthQuery::NftDossier {  } => query_nft_dossier(&deps, account),
         FtokenAuthQuery::StakedTokens {  } => query_staked_tokens(&deps.storage, account),
+        FtokenAuthQuery::UnbondingTokens {  } => query_unbonding_tokens(&deps.storage, account),
         FtokenAuthQuery::ReservationPriceVote {  } => query_reservation_price_vote(&deps.storage, account),
         FtokenAuthQuery::ProposalVotes { prop_id } => query_proposal_votes(&deps.storage, account, prop_id),
         FtokenAuthQuery::Bid {  } => query_bid(&deps.storage, account),
+        FtokenAuthQuery::Delegation {  } => query_delegation(&deps.storage, account),
+        FtokenAuthQuery::VotingPower {  } => query_voting_power(&deps.storage, account),
+        FtokenAuthQuery::VotingPowerAt { height } => query_voting_power_at(&deps.storage, account, height),
+        FtokenAuthQuery::UnclaimedProceeds {  } => query_unclaimed_proceeds(deps, account),
     }
 }
 
@@ -206,8 +278,12 @@ fn query_proposal_config<S: Storage>(
     storage: &S,
 ) -> QueryResult {
     let ftkn_conf = ftkn_config_r(storage).load()?;
+    let vote_thresholds = PropType::all().iter()
+        .map(|prop_type| ftkn_conf.prop_conf.thresholds(prop_type))
+        .collect();
     to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::ProposalConfig {
         prop_conf: ftkn_conf.prop_conf,
+        vote_thresholds,
     }))
 }
 
@@ -215,37 +291,101 @@ fn query_reservation_config<S: Storage>(
     storage: &S,
 ) -> QueryResult {
     let agg_resv = agg_resv_price_r(storage).load()?;
+    let method = ftkn_config_r(storage).load()?.auc_conf.resv_agg;
     to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::ReservationPrice {
         ftokens_voted: agg_resv.uint128_stake(),
-        reservation_price: agg_resv.uint128_price(),
+        reservation_price: curr_resv_price(storage, &method)?,
+        method,
+    }))
+}
+
+fn query_reservation_price_history<S: Storage>(
+    storage: &S,
+    page: u32,
+    page_size: u32,
+) -> QueryResult {
+    let (checkpoints, total_checkpoints) = get_resv_checkpoints(storage, page, page_size)?;
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::ReservationPriceHistory {
+        checkpoints,
+        total_checkpoints,
     }))
 }
 
+fn query_reservation_price_twap<S: Storage>(
+    storage: &S,
+    start_height: u64,
+    end_height: u64,
+) -> QueryResult {
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::ReservationPriceTwap {
+        twap: get_resv_twap(storage, start_height, end_height)?,
+    }))
+}
+
+/// max number of proposals that are scanned when the proposal list is filtered by status
+pub const MAX_PROP_LIST_SCAN: u32 = 500;
+
+/// Proposals are listed latest first. Only as many proposals as needed to fill the page 
+/// are loaded. When filtering by status, only the latest `MAX_PROP_LIST_SCAN` proposals, or 
+/// proposals by `proposer`, are scanned
 fn query_proposal_list<S: Storage>(
     storage: &S,
+    page: u32,
+    page_size: u32,
+    status: Option<PropStatus>,
+    proposer: Option<HumanAddr>,
 ) -> QueryResult {
-    let next_prop_id = prop_id_r(storage).load()?;
+    // prop_ids are listed by position: the position in the proposer's proposals, or the prop_id itself
+    let len = match &proposer {
+        Some(addr) => proposer_props_len(storage, addr)?,
+        None => prop_id_r(storage).load()?,
+    };
+    let prop_id_at = |pos: u32| match &proposer {
+        Some(addr) => get_proposer_prop(storage, addr, pos),
+        None => Ok(pos),
+    };
+    let vault_active = ftoken_info_r(storage).load()?.vault_active;
+    let load_prop = |pos: u32| -> StdResult<PropInfo> {
+        let mut prop_info = props_r(storage).load(&prop_id_at(pos)?.to_le_bytes())?;
+        prop_info.cancel_if_vault_closed(vault_active);
+        Ok(prop_info)
+    };
+
+    let skip = page.saturating_mul(page_size) as usize;
+    let props: Vec<PropInfo> = match &status {
+        // without a filter, only the proposals on the page are loaded
+        None => (0..len).rev()
+            .skip(skip)
+            .take(page_size as usize)
+            .map(load_prop)
+            .collect::<StdResult<_>>()?,
+        Some(status) => (0..len).rev()
+            .take(MAX_PROP_LIST_SCAN as usize)
+            .map(load_prop)
+            .filter(|prop_info| match prop_info {
+                Ok(prop_info) => prop_info.has_status(status),
+                Err(_) => true,
+            })
+            .skip(skip)
+            .take(page_size as usize)
+            .collect::<StdResult<_>>()?,
+    };
 
     let mut prop_info_tally_list = vec![];
-    {
-        let mut i = 0u32;
-        while i < next_prop_id {
-            let prop_info = props_r(storage).may_load(&i.to_le_bytes())?;
-            let vote_tally = votes_total_r(storage).may_load(&i.to_le_bytes())?;
-
-            if let Some(p) = prop_info {
-                if let Some(v) = vote_tally {
-                    prop_info_tally_list.push(
-                        PropInfoTally {
-                            prop_info: p,
-                            vote_tally: v,
-                        }
-                    )
-                }
-            };
-
-            i += 1;
-        }
+    for prop_info in props {
+        // secret ballot: only reveal turnout until the proposal is finalized
+        let vote_tally = votes_total_r(storage).load(&prop_info.prop_id.to_le_bytes())?;
+        let turnout = vote_tally.total();
+        let vote_tally = match prop_info.votes_hidden() {
+            true => None,
+            false => Some(vote_tally),
+        };
+        prop_info_tally_list.push(
+            PropInfoTally {
+                prop_info,
+                vote_tally,
+                turnout,
+            }
+        )
     }
 
     to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::ProposalList( 
@@ -253,6 +393,56 @@ fn query_proposal_list<S: Storage>(
     )))
 }
 
+fn query_treasury_balance<S: Storage>(
+    storage: &S,
+) -> QueryResult {
+    let treasury = treasury_r(storage).load()?;
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::TreasuryBalance(
+        treasury
+    )))
+}
+
+fn query_vault_status<S: Storage>(
+    storage: &S,
+) -> QueryResult {
+    let status = vault_status_r(storage).load()?;
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::VaultStatus(
+        status
+    )))
+}
+
+fn query_tender_offer_list<S: Storage>(
+    storage: &S,
+    page: u32,
+    page_size: u32,
+) -> QueryResult {
+    let offers = (0..tender_offer_id_r(storage).load()?).rev()
+        .skip(page.saturating_mul(page_size) as usize)
+        .take(page_size as usize)
+        .map(|offer_id| tender_offers_r(storage).load(&offer_id.to_le_bytes()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::TenderOfferList(
+        offers
+    )))
+}
+
+fn query_buyout_offer_list<S: Storage>(
+    storage: &S,
+    page: u32,
+    page_size: u32,
+) -> QueryResult {
+    let offers = (0..buyout_offer_id_r(storage).load()?).rev()
+        .skip(page.saturating_mul(page_size) as usize)
+        .take(page_size as usize)
+        .map(|offer_id| buyout_offers_r(storage).load(&offer_id.to_le_bytes()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::BuyoutOfferList(
+        offers
+    )))
+}
+
 fn query_bid_list<S: Storage>(
     storage: &S,
     page: u32,
@@ -336,6 +526,18 @@ fn query_staked_tokens<S:Storage>(
     )))
 }
 
+fn query_unbonding_tokens<S: Storage>(
+    storage: &S,
+    account: &HumanAddr,
+) -> QueryResult {
+    let entries = unbonding_r(storage).may_load(to_binary(&account)?.as_slice())?.unwrap_or_default();
+    let total = Uint128(entries.iter().map(|entry| entry.amount.u128()).sum());
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::UnbondingTokens {
+        entries,
+        total,
+    }))
+}
+
 fn query_reservation_price_vote<S: Storage>(
     storage: &S,
     account: &HumanAddr,
@@ -351,6 +553,7 @@ fn query_proposal_votes<S: Storage>(
     account: &HumanAddr,
     prop_id: u32,
 ) -> QueryResult {
+    // an address can always see its own vote, even if the proposal uses a secret ballot
     let vote = votes_r(storage, prop_id).load(&to_binary(&account)?.as_slice())?;
     to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::ProposalVotes(
         vote
@@ -372,6 +575,73 @@ fn query_bid<S: Storage>(
     )))
 } 
 
+fn query_delegation<S: Storage>(
+    storage: &S,
+    account: &HumanAddr,
+) -> QueryResult {
+    let delegation = delegs_r(storage).may_load(to_binary(account)?.as_slice())?.unwrap_or_default();
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::Delegation(
+        delegation
+    )))
+}
+
+fn query_voting_power<S: Storage>(
+    storage: &S,
+    account: &HumanAddr,
+) -> QueryResult {
+    let staked = ftkn_stake_r(storage).may_load(to_binary(account)?.as_slice())?.unwrap_or_default().amount;
+    let delegated = deleg_power_r(storage).may_load(to_binary(account)?.as_slice())?.unwrap_or_default();
+    let delegation = delegs_r(storage).may_load(to_binary(account)?.as_slice())?.unwrap_or_default();
+
+    // own staked ftokens only count if they have not been delegated
+    let own_power = | delegate: &Option<HumanAddr> | match delegate {
+        Some(_) => Uint128(0),
+        None => staked,
+    };
+    let proposal_power = own_power(&delegation.proposals) + delegated.proposals;
+    let resv_price_power = own_power(&delegation.resv_price) + delegated.resv_price;
+
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::VotingPower {
+        staked,
+        delegated,
+        proposal_power,
+        resv_price_power,
+    }))
+}
+
+fn query_voting_power_at<S: Storage>(
+    storage: &S,
+    account: &HumanAddr,
+    height: u64,
+) -> QueryResult {
+    let checkpoint = get_power_at(storage, account, height)?;
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::VotingPowerAt(
+        checkpoint
+    )))
+}
+
+fn query_unclaimed_proceeds<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    account: &HumanAddr,
+) -> QueryResult {
+    let ftokens = claimable_ftokens(deps, account)?.total();
+    let proceeds = match sale_proceeds_r(&deps.storage).may_load()? {
+        Some(sale_proceeds) => {
+            let treasury = treasury_r(&deps.storage).load()?;
+            let remaining_supply = proceeds_ftoken_supply(&deps.storage, &treasury)?;
+            let remaining_proceeds = sale_proceeds.amount.u128().saturating_sub(sale_proceeds.claimed.u128());
+            calc_redemption(ftokens, remaining_supply, remaining_proceeds)?
+        },
+        None => 0,
+    };
+    let claimed = proceeds_claims_r(&deps.storage).may_load(to_binary(account)?.as_slice())?.unwrap_or_default();
+    to_binary(&QueryAnswer::FtokenQueryAnswer(FtokenQueryAnswer::UnclaimedProceeds {
+        ftokens: Uint128(ftokens),
+        proceeds: Uint128(proceeds),
+        claimed,
+    }))
+}
+
 /////////////////////////////////////////////////////////////////////////////////
 // Private functions
 /////////////////////////////////////////////////////////////////////////////////
@@ -2,7 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{
-    Storage, Uint128, HumanAddr, StdResult, to_binary, 
+    Storage, Uint128, HumanAddr, StdResult, StdError, to_binary, 
 };
 use cosmwasm_storage::{
     PrefixedStorage, ReadonlyPrefixedStorage, 
@@ -15,7 +15,7 @@ use secret_toolkit::storage::{AppendStore, AppendStoreMut};
 use crate::{
     viewing_key::ViewingKey
 };
-use fsnft_utils::{FtokenInfo, FtokenConf, AucConf};
+use fsnft_utils::{FtokenInfo, FtokenConf, AucConf, VoteScheme, SlashDest, ContractInfo};
 
 use super::{
     msg::{
@@ -41,9 +41,38 @@ pub const NFT_VIEW_KEY: &[u8] = b"nftviewkey";
 pub const CURRENT_PROP_ID: &[u8] = b"currentproptid";
 pub const VOTES_BUCKET: &[u8] = b"votesbucket";
 pub const VOTES_TOTAL: &[u8] = b"votetotal";
+pub const VOTE_POWERS: &[u8] = b"votepowers";
+pub const VOTE_POWERS_TOTAL: &[u8] = b"votepowerstotal";
 pub const RESVPRICE_STORE: &[u8] = b"reservprice";
 pub const AGGRESVPRICE_STORE: &[u8] = b"aggresvprice";
 pub const AUCTION_INFO: &[u8] = b"auctioninfo";
+pub const DELEGATIONS: &[u8] = b"delegations";
+pub const DELEG_POWER: &[u8] = b"delegpower";
+pub const PROP_DELEG: &[u8] = b"propdeleg";
+pub const PREFIX_POWER_CHECKPOINTS: &[u8] = b"powercheckpoints";
+pub const ACTIVE_VOTES: &[u8] = b"activevotes";
+pub const TREASURY: &[u8] = b"treasury";
+pub const SLASH_CLAIMS: &[u8] = b"slashclaims";
+pub const SLASH_CLAIMED: &[u8] = b"slashclaimed";
+pub const PREFIX_PROPOSER_PROPS: &[u8] = b"proposerprops";
+pub const VAULT_STATUS: &[u8] = b"vaultstatus";
+pub const TENDER_OFFERS: &[u8] = b"tenderoffers";
+pub const CURRENT_TENDER_OFFER_ID: &[u8] = b"currenttenderofferid";
+pub const BUYOUT_OFFERS: &[u8] = b"buyoutoffers";
+pub const CURRENT_BUYOUT_OFFER_ID: &[u8] = b"currentbuyoutofferid";
+pub const SALE_PROCEEDS: &[u8] = b"saleproceeds";
+pub const RESV_LEVELS: &[u8] = b"resvlevels";
+pub const RESV_MEDIAN: &[u8] = b"resvmedian";
+pub const RESV_LEVEL_COUNT: &[u8] = b"resvlevelcount";
+pub const PREFIX_RESV_CHECKPOINTS: &[u8] = b"resvcheckpoints";
+pub const RESV_VOTE_HEIGHTS: &[u8] = b"resvvoteheights";
+pub const PREFIX_RESV_VOTE_QUEUE: &[u8] = b"resvvotequeue";
+pub const RESV_VOTE_CURSOR: &[u8] = b"resvvotecursor";
+pub const UNBONDING: &[u8] = b"unbonding";
+pub const PROCEEDS_CLAIMS: &[u8] = b"proceedsclaims";
+pub const PROCEEDS_CURSOR: &[u8] = b"proceedscursor";
+pub const BID_REFUND_CURSOR: &[u8] = b"bidrefundcursor";
+pub const ORPHANED_FTOKENS: &[u8] = b"orphanedftokens";
 
 
 
@@ -52,47 +81,145 @@ pub const AUCTION_INFO: &[u8] = b"auctioninfo";
 /////////////////////////////////////////////////////////////////////////////////
 
 /// staked ftokens
-pub fn ftkn_stake_w<S: Storage>(storage: &mut S) -> Bucket<S, StakedTokens> {
+pub fn ftkn_stake_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, StakedTokens> {
     bucket(FTKN_STAKE, storage)
 }
-pub fn ftkn_stake_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, StakedTokens> {
+pub fn ftkn_stake_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, StakedTokens> {
     bucket_read(FTKN_STAKE, storage)
 }
 
+/// unstaked ftokens that are unbonding, in the order they were unstaked (key: staker HumanAddr)
+pub fn unbonding_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, Vec<UnbondingEntry>> {
+    bucket(UNBONDING, storage)
+}
+pub fn unbonding_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Vec<UnbondingEntry>> {
+    bucket_read(UNBONDING, storage)
+}
+
+/// sale proceeds claimed by each address (key: claimant HumanAddr)
+pub fn proceeds_claims_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, ProceedsClaim> {
+    bucket(PROCEEDS_CLAIMS, storage)
+}
+pub fn proceeds_claims_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, ProceedsClaim> {
+    bucket_read(PROCEEDS_CLAIMS, storage)
+}
+
 /// Links a bidder's HumanAddr (key) with the u32 pos (value here, key in the AppendStore storage).
 /// Shares namespace the bid AppendStore storage, but should have no collision because this uses
 /// HumanAddr as keys, and AppendStore uses pos: u32 as keys
-fn bids_w<S: Storage>(storage: &mut S) -> Bucket<S, u32> {
+fn bids_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, u32> {
     bucket(PREFIX_BIDS, storage)
 }
-fn bids_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, u32> {
+fn bids_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, u32> {
     bucket_read(PREFIX_BIDS, storage)
 }
 
 /// Proposal storage: stores proposal information
-pub fn props_w<S: Storage>(storage: &mut S) -> Bucket<S, PropInfo> {
+pub fn props_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, PropInfo> {
     bucket(PROPS_STORE, storage)
 }
-pub fn props_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, PropInfo> {
+pub fn props_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, PropInfo> {
     bucket_read(PROPS_STORE, storage)
 }
 
 /// proposal vote tally, which is the running cumulative tally
-pub fn votes_total_w<S: Storage>(storage: &mut S) -> Bucket<S, VoteRegister> {
+pub fn votes_total_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, VoteRegister> {
     bucket(VOTES_TOTAL, storage)
 }
-pub fn votes_total_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, VoteRegister> {
+pub fn votes_total_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, VoteRegister> {
     bucket_read(VOTES_TOTAL, storage)
 }
 
+/// proposal tally of voting power before applying the proposal's vote scheme, which vote thresholds
+/// relative to the total supply are measured with
+pub fn vote_powers_total_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, VoteRegister> {
+    bucket(VOTE_POWERS_TOTAL, storage)
+}
+pub fn vote_powers_total_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, VoteRegister> {
+    bucket_read(VOTE_POWERS_TOTAL, storage)
+}
+
+/// slashed proposal stake claimed so far by veto voters (key: prop_id)
+pub fn slash_claimed_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, SlashClaimTally> {
+    bucket(SLASH_CLAIMED, storage)
+}
+pub fn slash_claimed_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, SlashClaimTally> {
+    bucket_read(SLASH_CLAIMED, storage)
+}
+
+/// Tender offer storage: stores tender offer information
+pub fn tender_offers_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, TenderOffer> {
+    bucket(TENDER_OFFERS, storage)
+}
+pub fn tender_offers_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, TenderOffer> {
+    bucket_read(TENDER_OFFERS, storage)
+}
+
+/// Buyout offer storage: stores buyout offer information
+pub fn buyout_offers_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, BuyoutOffer> {
+    bucket(BUYOUT_OFFERS, storage)
+}
+pub fn buyout_offers_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, BuyoutOffer> {
+    bucket_read(BUYOUT_OFFERS, storage)
+}
+
+/// Reservation price votes aggregated by price, linked in price order. Keys are big-endian prices
+fn resv_levels_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, ResvLevel> {
+    bucket(RESV_LEVELS, storage)
+}
+fn resv_levels_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, ResvLevel> {
+    bucket_read(RESV_LEVELS, storage)
+}
+
+/// number of linked reservation price levels
+fn resv_level_count_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, u32> {
+    singleton(storage, RESV_LEVEL_COUNT)
+}
+fn resv_level_count_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u32> {
+    singleton_read(storage, RESV_LEVEL_COUNT)
+}
+
 /// Reservation price votes for each address
-pub fn resv_price_w<S: Storage>(storage: &mut S) -> Bucket<S, ResvVote> {
+pub fn resv_price_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, ResvVote> {
     bucket(RESVPRICE_STORE, storage)
 }
-pub fn resv_price_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, ResvVote> {
+pub fn resv_price_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, ResvVote> {
     bucket_read(RESVPRICE_STORE, storage)
 }
 
+/// block height of each address' last reservation price vote
+pub fn resv_vote_heights_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, u64> {
+    bucket(RESV_VOTE_HEIGHTS, storage)
+}
+pub fn resv_vote_heights_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, u64> {
+    bucket_read(RESV_VOTE_HEIGHTS, storage)
+}
+
+/// Delegations made by each staker (key: delegator HumanAddr)
+pub fn delegs_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, Delegation> {
+    bucket(DELEGATIONS, storage)
+}
+pub fn delegs_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Delegation> {
+    bucket_read(DELEGATIONS, storage)
+}
+
+/// Index of each address' votes on proposals that may still be in their voting period, so
+/// votes can be re-weighted when voting power changes (key: voter HumanAddr)
+pub fn active_votes_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, Vec<ActiveVote>> {
+    bucket(ACTIVE_VOTES, storage)
+}
+pub fn active_votes_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Vec<ActiveVote>> {
+    bucket_read(ACTIVE_VOTES, storage)
+}
+
+/// Staked ftokens delegated to each address (key: delegate HumanAddr)
+pub fn deleg_power_w<S: Storage>(storage: &mut S) -> Bucket<'_, S, DelegatedPower> {
+    bucket(DELEG_POWER, storage)
+}
+pub fn deleg_power_r<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, DelegatedPower> {
+    bucket_read(DELEG_POWER, storage)
+}
+
 
 /////////////////////////////////////////////////////////////////////////////////
 // Multi-level Buckets
@@ -102,16 +229,61 @@ pub fn resv_price_r<S: Storage>(storage: &S) -> ReadonlyBucket<S, ResvVote> {
 pub fn votes_w<S: Storage>(
     storage: &mut S,
     prop_id: u32
-) -> Bucket<S, VoteRegister> {
+) -> Bucket<'_, S, VoteRegister> {
     Bucket::multilevel(&[VOTES_BUCKET, &prop_id.to_le_bytes()], storage)
 }
 pub fn votes_r<S: Storage>(
     storage: &S,
     prop_id: u32
-) -> ReadonlyBucket<S, VoteRegister> {
+) -> ReadonlyBucket<'_, S, VoteRegister> {
     ReadonlyBucket::multilevel(&[VOTES_BUCKET, &prop_id.to_le_bytes()], storage)
 }
 
+/// Multilevel bucket to store the voting power of proposal votes, before applying the proposal's 
+/// vote scheme. Key intended to be [`prop_id`, HumanAddr]  
+pub fn vote_powers_w<S: Storage>(
+    storage: &mut S,
+    prop_id: u32
+) -> Bucket<'_, S, VoteRegister> {
+    Bucket::multilevel(&[VOTE_POWERS, &prop_id.to_le_bytes()], storage)
+}
+pub fn vote_powers_r<S: Storage>(
+    storage: &S,
+    prop_id: u32
+) -> ReadonlyBucket<'_, S, VoteRegister> {
+    ReadonlyBucket::multilevel(&[VOTE_POWERS, &prop_id.to_le_bytes()], storage)
+}
+
+/// Multilevel bucket to store delegated weight used on a proposal. Key intended to be 
+/// [`prop_id`, delegate HumanAddr]  
+pub fn prop_deleg_w<S: Storage>(
+    storage: &mut S,
+    prop_id: u32
+) -> Bucket<'_, S, PropDelegTally> {
+    Bucket::multilevel(&[PROP_DELEG, &prop_id.to_le_bytes()], storage)
+}
+pub fn prop_deleg_r<S: Storage>(
+    storage: &S,
+    prop_id: u32
+) -> ReadonlyBucket<'_, S, PropDelegTally> {
+    ReadonlyBucket::multilevel(&[PROP_DELEG, &prop_id.to_le_bytes()], storage)
+}
+
+/// Multilevel bucket to store slashed proposal stake claimed by veto voters. Key intended 
+/// to be [`prop_id`, voter HumanAddr]  
+pub fn slash_claims_w<S: Storage>(
+    storage: &mut S,
+    prop_id: u32
+) -> Bucket<'_, S, Uint128> {
+    Bucket::multilevel(&[SLASH_CLAIMS, &prop_id.to_le_bytes()], storage)
+}
+pub fn slash_claims_r<S: Storage>(
+    storage: &S,
+    prop_id: u32
+) -> ReadonlyBucket<'_, S, Uint128> {
+    ReadonlyBucket::multilevel(&[SLASH_CLAIMS, &prop_id.to_le_bytes()], storage)
+}
+
 
 /////////////////////////////////////////////////////////////////////////////////
 // Singletons
@@ -119,54 +291,134 @@ pub fn votes_r<S: Storage>(
 
 
 /// FtokenContr storage: stores information on this ftokens contract
-pub fn ftoken_info_w<S: Storage>(storage: &mut S) -> Singleton<S, FtokenInfo> {
+pub fn ftoken_info_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, FtokenInfo> {
     singleton(storage, FTOKEN_CONTR_FTKN)
 }
-pub fn ftoken_info_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, FtokenInfo> {
+pub fn ftoken_info_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, FtokenInfo> {
     singleton_read( storage, FTOKEN_CONTR_FTKN)
 }
 
 /// config specifically for ftoken functionality
-pub fn ftkn_config_w<S: Storage>(storage: &mut S) -> Singleton<S, FtokenConf> {
+pub fn ftkn_config_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, FtokenConf> {
     singleton(storage, FTKN_CONFIG)
 }
-pub fn ftkn_config_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, FtokenConf> {
+pub fn ftkn_config_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, FtokenConf> {
     singleton_read( storage, FTKN_CONFIG)
 }
 
 /// index the next proposal to be received 
-pub fn prop_id_w<S: Storage>(storage: &mut S) -> Singleton<S, u32> {
+pub fn prop_id_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, u32> {
     singleton(storage, CURRENT_PROP_ID)
 }
-pub fn prop_id_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, u32> {
+pub fn prop_id_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u32> {
     singleton_read( storage, CURRENT_PROP_ID)
 }
 
+/// index the next tender offer to be received
+pub fn tender_offer_id_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, u32> {
+    singleton(storage, CURRENT_TENDER_OFFER_ID)
+}
+pub fn tender_offer_id_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u32> {
+    singleton_read(storage, CURRENT_TENDER_OFFER_ID)
+}
+
+/// index the next buyout offer to be received
+pub fn buyout_offer_id_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, u32> {
+    singleton(storage, CURRENT_BUYOUT_OFFER_ID)
+}
+pub fn buyout_offer_id_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u32> {
+    singleton_read(storage, CURRENT_BUYOUT_OFFER_ID)
+}
+
+/// proceeds from the sale of the underlying NFT, saved when the vault closes
+pub fn sale_proceeds_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, SaleProceeds> {
+    singleton(storage, SALE_PROCEEDS)
+}
+pub fn sale_proceeds_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, SaleProceeds> {
+    singleton_read(storage, SALE_PROCEEDS)
+}
+
+/// stake-weighted median of reservation price votes
+pub fn resv_median_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, ResvMedian> {
+    singleton(storage, RESV_MEDIAN)
+}
+pub fn resv_median_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, ResvMedian> {
+    singleton_read(storage, RESV_MEDIAN)
+}
+
+/// position in the reservation price vote queue of the next vote to check for expiry
+pub fn resv_vote_cursor_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, u32> {
+    singleton(storage, RESV_VOTE_CURSOR)
+}
+pub fn resv_vote_cursor_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u32> {
+    singleton_read(storage, RESV_VOTE_CURSOR)
+}
+
+/// position in the holder index that `DistributeProceeds` resumes from
+pub fn proceeds_cursor_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, u32> {
+    singleton(storage, PROCEEDS_CURSOR)
+}
+pub fn proceeds_cursor_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u32> {
+    singleton_read(storage, PROCEEDS_CURSOR)
+}
+
+/// position in the bid store that `RefundLosingBids` resumes from
+pub fn bid_refund_cursor_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, u32> {
+    singleton(storage, BID_REFUND_CURSOR)
+}
+pub fn bid_refund_cursor_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, u32> {
+    singleton_read(storage, BID_REFUND_CURSOR)
+}
+
+/// ftokens transferred directly to this contract, which no address can retrieve
+pub fn orphaned_ftkn_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, Uint128> {
+    singleton(storage, ORPHANED_FTOKENS)
+}
+pub fn orphaned_ftkn_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, Uint128> {
+    singleton_read(storage, ORPHANED_FTOKENS)
+}
+
 /// information on auction
-pub fn auction_info_w<S: Storage>(storage: &mut S) -> Singleton<S, AuctionInfo> {
+pub fn auction_info_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, AuctionInfo> {
     singleton(storage, AUCTION_INFO)
 }
-pub fn auction_info_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, AuctionInfo> {
+pub fn auction_info_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, AuctionInfo> {
     singleton_read( storage, AUCTION_INFO)
 }
 
 /// stores viewing key to query nft contract
-pub fn nft_vk_w<S: Storage>(storage: &mut S) -> Singleton<S, ViewingKey> {
+pub fn nft_vk_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, ViewingKey> {
     singleton(storage, NFT_VIEW_KEY)
 }
 
-pub fn nft_vk_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, ViewingKey> {
+pub fn nft_vk_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, ViewingKey> {
     singleton_read(storage, NFT_VIEW_KEY)
 }
 
 /// Aggregate reservation price 
-pub fn agg_resv_price_w<S: Storage>(storage: &mut S) -> Singleton<S, ResvVote> {
+pub fn agg_resv_price_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, ResvVote> {
     singleton(storage, AGGRESVPRICE_STORE)
 }
-pub fn agg_resv_price_r<S: Storage>(storage: &S) -> ReadonlySingleton<S, ResvVote> {
+pub fn agg_resv_price_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, ResvVote> {
     singleton_read(storage, AGGRESVPRICE_STORE)
 }
 
+/// Vault treasury balances
+pub fn treasury_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, Treasury> {
+    singleton(storage, TREASURY)
+}
+pub fn treasury_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, Treasury> {
+    singleton_read(storage, TREASURY)
+}
+
+/// Vault status, which determines which vault operations are paused
+pub fn vault_status_w<S: Storage>(storage: &mut S) -> Singleton<'_, S, VaultStatus> {
+    singleton(storage, VAULT_STATUS)
+}
+pub fn vault_status_r<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, VaultStatus> {
+    singleton_read(storage, VAULT_STATUS)
+}
+
 /////////////////////////////////////////////////////////////////////////////////
 // Appendstore
 /////////////////////////////////////////////////////////////////////////////////
@@ -218,6 +470,22 @@ pub fn get_bid<S: Storage>(
     store.get_at(pos)
 }
 
+/// Returns the bid at position `pos`, if any
+pub fn may_get_bid<S: Storage>(
+    store: &S,
+    pos: u32,
+) -> StdResult<Option<BidInfo>> {
+    let store = ReadonlyPrefixedStorage::new(PREFIX_BIDS, store);
+    let store = match AppendStore::<BidInfo, _, _>::attach(&store) {
+        Some(result) => result?,
+        None => return Ok(None),
+    };
+    match pos < store.len() {
+        true => Ok(Some(store.get_at(pos)?)),
+        false => Ok(None),
+    }
+}
+
 pub fn may_get_bid_from_addr<S: Storage>(
     store: &S,
     addr: &HumanAddr,
@@ -266,6 +534,392 @@ pub fn get_bids<S: Storage>(
     bids.map(|bids| (bids, store.len() as u64))
 }
 
+// voting power checkpoints: one Appendstore per address, in ascending block height
+// -----------------------------------------------------------------------------
+/// Saves a checkpoint of an address' voting power. Overwrites the last checkpoint if it 
+/// has the same height
+pub fn push_power_checkpoint<S: Storage>(
+    store: &mut S,
+    addr: &HumanAddr,
+    checkpoint: &PowerCheckpoint,
+) -> StdResult<()> {
+    let addr_bin = to_binary(addr)?;
+    let mut store = PrefixedStorage::multilevel(&[PREFIX_POWER_CHECKPOINTS, addr_bin.as_slice()], store);
+    let mut store = AppendStoreMut::<PowerCheckpoint, _>::attach_or_create(&mut store)?;
+    let len = store.len();
+    if len > 0 && store.get_at(len - 1)?.height == checkpoint.height {
+        store.set_at(len - 1, checkpoint)
+    } else {
+        store.push(checkpoint)
+    }
+}
+
+/// Returns the voting power of an address at a given block height, which is the last
+/// checkpoint at or before that height. Uses binary search
+pub fn get_power_at<S: Storage>(
+    store: &S,
+    addr: &HumanAddr,
+    height: u64,
+) -> StdResult<PowerCheckpoint> {
+    let addr_bin = to_binary(addr)?;
+    let store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_POWER_CHECKPOINTS, addr_bin.as_slice()], store);
+    let store = match AppendStore::<PowerCheckpoint, _, _>::attach(&store) {
+        Some(result) => result?,
+        None => return Ok(PowerCheckpoint::default()),
+    };
+
+    // number of checkpoints at or before `height`
+    let mut low = 0u32;
+    let mut high = store.len();
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if store.get_at(mid)?.height <= height {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    match low {
+        0 => Ok(PowerCheckpoint::default()),
+        _ => store.get_at(low - 1),
+    }
+}
+
+// reservation price checkpoints: Appendstore in ascending order of height, with at most one
+// checkpoint per block height
+// -----------------------------------------------------------------------------
+pub fn push_resv_checkpoint<S: Storage>(
+    store: &mut S,
+    checkpoint: &ResvCheckpoint,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::new(PREFIX_RESV_CHECKPOINTS, store);
+    let mut store = AppendStoreMut::<ResvCheckpoint, _>::attach_or_create(&mut store)?;
+    let len = store.len();
+    if len > 0 {
+        let last = store.get_at(len - 1)?;
+        if last.price == checkpoint.price && last.stake == checkpoint.stake {
+            return Ok(())
+        } else if last.height == checkpoint.height {
+            return store.set_at(len - 1, checkpoint)
+        }
+    }
+    store.push(checkpoint)
+}
+
+/// Returns a page of reservation price checkpoints, latest first, and the total number of checkpoints
+pub fn get_resv_checkpoints<S: Storage>(
+    store: &S,
+    page: u32,
+    page_size: u32,
+) -> StdResult<(Vec<ResvCheckpoint>, u32)> {
+    let store = ReadonlyPrefixedStorage::new(PREFIX_RESV_CHECKPOINTS, store);
+    let store = match AppendStore::<ResvCheckpoint, _, _>::attach(&store) {
+        Some(result) => result?,
+        None => return Ok((vec![], 0)),
+    };
+    let checkpoints = store.iter().rev()
+        .skip(page.saturating_mul(page_size) as usize)
+        .take(page_size as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok((checkpoints, store.len()))
+}
+
+/// Time-weighted average reservation price from `start` (inclusive) to `end` (exclusive) block height. 
+/// Each checkpoint's price holds until the next checkpoint, and the earliest checkpoint's price is used 
+/// before it. Uses binary search to find the checkpoint at `start`
+pub fn get_resv_twap<S: Storage>(
+    store: &S,
+    start: u64,
+    end: u64,
+) -> StdResult<Uint128> {
+    if end <= start {
+        return Err(StdError::generic_err("end height must be greater than start height"))
+    }
+    let store = ReadonlyPrefixedStorage::new(PREFIX_RESV_CHECKPOINTS, store);
+    let store = match AppendStore::<ResvCheckpoint, _, _>::attach(&store) {
+        Some(result) => result?,
+        None => return Err(StdError::generic_err("no reservation price checkpoints")),
+    };
+
+    // number of checkpoints at or before `start`
+    let mut low = 0u32;
+    let mut high = store.len();
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if store.get_at(mid)?.height <= start {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    let mut idx = low.saturating_sub(1);
+    let mut curr = store.get_at(idx)?;
+    let mut from = start;
+    let mut price_blocks = U256::zero();
+    loop {
+        let next = match idx + 1 < store.len() {
+            true => Some(store.get_at(idx + 1)?),
+            false => None,
+        };
+        let to = next.as_ref().map_or(end, |i| i.height.min(end));
+        price_blocks += U256::from(curr.price.u128()) * U256::from(to - from);
+        match next {
+            Some(i) if to < end => {
+                from = to;
+                idx += 1;
+                curr = i;
+            },
+            _ => break,
+        }
+    }
+    Ok(Uint128((price_blocks / U256::from(end - start)).low_u128()))
+}
+
+// reservation price vote queue: Appendstore of reservation price votes in the order they were 
+// cast, so votes expire in queue order
+// -----------------------------------------------------------------------------
+pub fn push_resv_vote_entry<S: Storage>(
+    store: &mut S,
+    entry: &ResvVoteEntry,
+) -> StdResult<()> {
+    let mut store = PrefixedStorage::new(PREFIX_RESV_VOTE_QUEUE, store);
+    let mut store = AppendStoreMut::<ResvVoteEntry, _>::attach_or_create(&mut store)?;
+    store.push(entry)
+}
+
+/// Returns the entry at position `pos` of the queue, if any
+pub fn may_get_resv_vote_entry<S: Storage>(
+    store: &S,
+    pos: u32,
+) -> StdResult<Option<ResvVoteEntry>> {
+    let store = ReadonlyPrefixedStorage::new(PREFIX_RESV_VOTE_QUEUE, store);
+    let store = match AppendStore::<ResvVoteEntry, _, _>::attach(&store) {
+        Some(result) => result?,
+        None => return Ok(None),
+    };
+    match pos < store.len() {
+        true => Ok(Some(store.get_at(pos)?)),
+        false => Ok(None),
+    }
+}
+
+// proposals by proposer: one Appendstore of prop_ids per proposer, in ascending order
+// -----------------------------------------------------------------------------
+pub fn push_proposer_prop<S: Storage>(
+    store: &mut S,
+    proposer: &HumanAddr,
+    prop_id: u32,
+) -> StdResult<()> {
+    let addr_bin = to_binary(proposer)?;
+    let mut store = PrefixedStorage::multilevel(&[PREFIX_PROPOSER_PROPS, addr_bin.as_slice()], store);
+    let mut store = AppendStoreMut::<u32, _>::attach_or_create(&mut store)?;
+    store.push(&prop_id)
+}
+
+/// Returns the prop_ids of all proposals made by a proposer, in ascending order
+pub fn get_proposer_props<S: Storage>(
+    store: &S,
+    proposer: &HumanAddr,
+) -> StdResult<Vec<u32>> {
+    let addr_bin = to_binary(proposer)?;
+    let store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_PROPOSER_PROPS, addr_bin.as_slice()], store);
+    let store = match AppendStore::<u32, _, _>::attach(&store) {
+        Some(result) => result?,
+        None => return Ok(vec![]),
+    };
+    store.iter().collect()
+}
+
+/// Returns the number of proposals made by a proposer
+pub fn proposer_props_len<S: Storage>(
+    store: &S,
+    proposer: &HumanAddr,
+) -> StdResult<u32> {
+    let addr_bin = to_binary(proposer)?;
+    let store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_PROPOSER_PROPS, addr_bin.as_slice()], store);
+    match AppendStore::<u32, _, _>::attach(&store) {
+        Some(result) => Ok(result?.len()),
+        None => Ok(0),
+    }
+}
+
+/// Returns the prop_id of the proposal at position `pos` of the proposals made by a proposer
+pub fn get_proposer_prop<S: Storage>(
+    store: &S,
+    proposer: &HumanAddr,
+    pos: u32,
+) -> StdResult<u32> {
+    let addr_bin = to_binary(proposer)?;
+    let store = ReadonlyPrefixedStorage::multilevel(&[PREFIX_PROPOSER_PROPS, addr_bin.as_slice()], store);
+    match AppendStore::<u32, _, _>::attach(&store) {
+        Some(result) => result?.get_at(pos),
+        None => Err(StdError::generic_err("proposer has no proposals")),
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////////
+// Reservation price median
+/////////////////////////////////////////////////////////////////////////////////
+
+// Price levels form a linked list in price order. The median points to one price level, and
+// keeps the stake above and below it, so each vote only walks the levels between its price and 
+// the median, rather than all voters. The number of price levels is capped, which bounds each walk
+// -----------------------------------------------------------------------------
+
+/// maximum number of distinct prices that reservation price votes can be cast at
+pub const MAX_RESV_LEVELS: u32 = 200;
+
+/// adds stake voting for `price` to the reservation price median
+pub fn add_resv_stake<S: Storage>(
+    storage: &mut S,
+    price: Uint128,
+    stake: Uint128,
+) -> StdResult<()> {
+    let mut median = resv_median_r(storage).may_load()?.unwrap_or_default();
+    let key = price.u128().to_be_bytes();
+    let mut level = match resv_levels_r(storage).may_load(&key)? {
+        Some(i) => i,
+        None => insert_resv_level(storage, &median, price)?,
+    };
+    level.stake += stake;
+    resv_levels_w(storage).save(&key, &level)?;
+
+    match median.median {
+        None => median.median = Some(price),
+        Some(curr) if price < curr => median.below += stake,
+        Some(curr) if price > curr => median.above += stake,
+        Some(_) => (),
+    }
+    rebalance_resv_median(storage, median)
+}
+
+/// removes stake voting for `price` from the reservation price median
+pub fn remove_resv_stake<S: Storage>(
+    storage: &mut S,
+    price: Uint128,
+    stake: Uint128,
+) -> StdResult<()> {
+    let mut median = resv_median_r(storage).may_load()?.unwrap_or_default();
+    let key = price.u128().to_be_bytes();
+    let mut level = resv_levels_r(storage).load(&key)?;
+    level.stake = (level.stake - stake)?;
+    resv_levels_w(storage).save(&key, &level)?;
+
+    match median.median {
+        Some(curr) if price < curr => median.below = (median.below - stake)?,
+        Some(curr) if price > curr => median.above = (median.above - stake)?,
+        _ => (),
+    }
+    if level.stake == Uint128(0) && median.median != Some(price) {
+        unlink_resv_level(storage, price)?;
+    }
+    rebalance_resv_median(storage, median)
+}
+
+/// links a new, empty price level, walking from the median to find its neighbours. Fails if there
+/// are already `MAX_RESV_LEVELS` price levels
+fn insert_resv_level<S: Storage>(
+    storage: &mut S,
+    median: &ResvMedian,
+    price: Uint128,
+) -> StdResult<ResvLevel> {
+    let count = resv_level_count_r(storage).may_load()?.unwrap_or_default();
+    if count >= MAX_RESV_LEVELS {
+        return Err(StdError::generic_err(format!(
+            "reservation price votes are limited to {} distinct prices. Vote for a price that already has votes", 
+            MAX_RESV_LEVELS,
+        )))
+    }
+    resv_level_count_w(storage).save(&(count + 1))?;
+
+    let (mut prev, mut next) = (None, None);
+    if let Some(curr) = median.median {
+        if price > curr {
+            prev = Some(curr);
+            next = resv_levels_r(storage).load(&curr.u128().to_be_bytes())?.next;
+            while let Some(i) = next.filter(|i| *i < price) {
+                prev = Some(i);
+                next = resv_levels_r(storage).load(&i.u128().to_be_bytes())?.next;
+            }
+        } else {
+            next = Some(curr);
+            prev = resv_levels_r(storage).load(&curr.u128().to_be_bytes())?.prev;
+            while let Some(i) = prev.filter(|i| *i > price) {
+                next = Some(i);
+                prev = resv_levels_r(storage).load(&i.u128().to_be_bytes())?.prev;
+            }
+        }
+    }
+    set_resv_link(storage, prev, |level| level.next = Some(price))?;
+    set_resv_link(storage, next, |level| level.prev = Some(price))?;
+
+    Ok(ResvLevel { stake: Uint128(0), prev, next })
+}
+
+/// removes an empty price level, linking its neighbours to each other
+fn unlink_resv_level<S: Storage>(
+    storage: &mut S,
+    price: Uint128,
+) -> StdResult<()> {
+    let key = price.u128().to_be_bytes();
+    let level = resv_levels_r(storage).load(&key)?;
+    set_resv_link(storage, level.prev, |i| i.next = level.next)?;
+    set_resv_link(storage, level.next, |i| i.prev = level.prev)?;
+    resv_levels_w(storage).remove(&key);
+    let count = resv_level_count_r(storage).load()?;
+    resv_level_count_w(storage).save(&count.saturating_sub(1))?;
+    Ok(())
+}
+
+fn set_resv_link<S: Storage, F: FnOnce(&mut ResvLevel)>(
+    storage: &mut S,
+    price: Option<Uint128>,
+    f: F,
+) -> StdResult<()> {
+    if let Some(price) = price {
+        let key = price.u128().to_be_bytes();
+        let mut level = resv_levels_r(storage).load(&key)?;
+        f(&mut level);
+        resv_levels_w(storage).save(&key, &level)?;
+    }
+    Ok(())
+}
+
+/// moves the median one price level at a time, until less than half the stake is below the median
+/// price level, and at least half is at or below it
+fn rebalance_resv_median<S: Storage>(
+    storage: &mut S,
+    mut median: ResvMedian,
+) -> StdResult<()> {
+    while let Some(curr) = median.median {
+        let level = resv_levels_r(storage).load(&curr.u128().to_be_bytes())?;
+        let (below, at, above) = (median.below.u128(), level.stake.u128(), median.above.u128());
+        if below == 0 && at == 0 && above == 0 {
+            unlink_resv_level(storage, curr)?;
+            median = ResvMedian::default();
+        } else if below >= at + above {
+            let prev = level.prev.ok_or_else(|| StdError::generic_err("reservation price levels are inconsistent"))?;
+            let prev_stake = resv_levels_r(storage).load(&prev.u128().to_be_bytes())?.stake;
+            median = ResvMedian { median: Some(prev), below: (median.below - prev_stake)?, above: Uint128(above + at) };
+            if at == 0 {
+                unlink_resv_level(storage, curr)?;
+            }
+        } else if below + at < above {
+            let next = level.next.ok_or_else(|| StdError::generic_err("reservation price levels are inconsistent"))?;
+            let next_stake = resv_levels_r(storage).load(&next.u128().to_be_bytes())?.stake;
+            median = ResvMedian { median: Some(next), below: Uint128(below + at), above: (median.above - next_stake)? };
+            if at == 0 {
+                unlink_resv_level(storage, curr)?;
+            }
+        } else {
+            break
+        }
+    }
+    resv_median_w(storage).save(&median)
+}
+
+
 /////////////////////////////////////////////////////////////////////////////////
 // Structs and enums
 /////////////////////////////////////////////////////////////////////////////////
@@ -285,6 +939,13 @@ impl Default for StakedTokens {
     }
 }
 
+/// Unstaked ftokens that can be claimed from `mature_height`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondingEntry {
+    pub amount: Uint128,
+    pub mature_height: u64,
+}
+
 /// Vote cast on proposals 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -309,6 +970,103 @@ pub struct VoteRegister {
     pub abstain: Uint128,
 }
 
+impl VoteRegister {
+    /// sum of `yes`, `no`, `veto` and `abstain` votes
+    pub fn total(&self) -> Uint128 {
+        self.yes + self.no + self.veto + self.abstain
+    }
+}
+
+/// Addresses that a staker has delegated its voting weight to. A staker can still vote 
+/// directly on individual proposals, which overrides its proposal delegate for that proposal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Delegation {
+    /// address which votes on proposals on behalf of the staker 
+    pub proposals: Option<HumanAddr>,
+    /// address which votes on the reservation price on behalf of the staker 
+    pub resv_price: Option<HumanAddr>,
+}
+
+/// Staked ftokens that other addresses have delegated to an address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct DelegatedPower {
+    /// ftokens delegated for voting on proposals
+    pub proposals: Uint128,
+    /// ftokens delegated for voting on the reservation price
+    pub resv_price: Uint128,
+    /// delegated ftokens are bonded until this height, because the delegate has used 
+    /// them to vote on proposals
+    pub unlock_height: u64,
+}
+
+/// Checkpoint of an address' voting power on proposals, saved whenever it changes. Votes on a
+/// proposal are weighted by voting power at the proposal's snapshot height
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PowerCheckpoint {
+    /// block height of the checkpoint
+    pub height: u64,
+    /// ftokens staked by the address
+    pub staked: Uint128,
+    /// ftokens delegated to the address for voting on proposals
+    pub delegated: Uint128,
+    /// address that this address delegated its proposal votes to
+    pub delegate: Option<HumanAddr>,
+}
+
+/// Slashed stake of a proposal claimed so far by veto voters, and the veto voting power it was claimed with
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct SlashClaimTally {
+    pub veto: Uint128,
+    pub amount: Uint128,
+}
+
+/// Assets held by the ftoken contract on behalf of ftoken holders. Tracked separately from
+/// staked ftokens, proposal stakes and bids, which the contract also holds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Treasury {
+    /// balances of registered SNIP20 tokens
+    pub snip20s: Vec<TreasuryBalance>,
+    /// ftokens held by the treasury. These are excluded from the supply that sale proceeds
+    /// are distributed over
+    pub ftoken: Uint128,
+}
+
+impl Treasury {
+    /// registered SNIP20 token with the given contract address
+    pub fn snip20_mut(&mut self, address: &HumanAddr) -> Option<&mut TreasuryBalance> {
+        self.snip20s.iter_mut().find(|i| &i.token.address == address)
+    }
+}
+
+/// Balance of a SNIP20 token registered with the treasury
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreasuryBalance {
+    pub token: ContractInfo,
+    pub balance: Uint128,
+    /// amount distributed to ftoken holders claiming proceeds after the vault closed
+    pub distributed: Uint128,
+}
+
+/// A vote cast by an address on a proposal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ActiveVote {
+    pub prop_id: u32,
+    pub vote: Vote,
+    /// voting power counted in the vote, before applying the proposal's vote scheme
+    pub power: Uint128,
+    /// block height from which voting power has been committed to the vote
+    pub height: u64,
+}
+
+/// Delegated weight of a delegate on a given proposal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PropDelegTally {
+    /// delegated ftokens counted in the delegate's current vote
+    pub counted: Uint128,
+    /// delegated ftokens of delegators that voted directly on the proposal
+    pub overridden: Uint128,
+}
+
 // /// vote count, weighted by staked ftokens todo!() remove duplicate struct
 // #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
 // pub struct TotalVotes {
@@ -323,12 +1081,54 @@ pub enum VoteResult {
     Won,
     Lost,
     LostWithVeto,
+    /// cancelled by the proposer, or automatically when the vault closed. Stake is refunded
+    Cancelled,
+}
+
+/// Proposal status, used to filter the proposal list
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PropStatus {
+    /// not yet finalized. This includes proposals whose voting period has ended, but which 
+    /// have not been finalized
+    Active,
+    /// finalized with outcome `Won`, whether or not it was executed. A passed proposal is not 
+    /// executed if it can no longer be, such as a buyout offer that expired before finalization
+    Passed,
+    /// passed, and executed when finalized
+    Executed,
+    /// finalized with outcome `Lost`
+    Lost,
+    /// finalized with outcome `LostWithVeto`
+    Vetoed,
+    /// cancelled by the proposer, or when the vault closed
+    Cancelled,
+}
+
+/// Vault status, set by a proposal or by a guardian. Exits, such as retrieving bids, unstaking and 
+/// claiming proceeds, remain open regardless of the vault status
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultStatus {
+    /// all operations are allowed
+    Normal,
+    /// bids, reservation price votes and auction finalization are paused
+    StopAuction,
+    /// proposals, proposal votes, proposal finalization and delegation are paused. Proposals to 
+    /// change the vault status remain open
+    StopGovernance,
+    /// all operations other than exits are paused, including staking and treasury deposits. Proposals 
+    /// to change the vault status remain open
+    StopAll,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PropInfoTally {
     pub prop_info: PropInfo,
-    pub vote_tally: VoteRegister,
+    /// `None` if the proposal uses a secret ballot and has not been finalized, or was cancelled
+    pub vote_tally: Option<VoteRegister>,
+    /// total ftoken-weighted votes cast
+    pub turnout: Uint128,
 }
 
 /// proposal information as stored by ftoken contract
@@ -338,6 +1138,12 @@ pub struct PropInfo {
     pub prop_id: u32,
     /// address of proposer
     pub proposer: HumanAddr,
+    /// proposal title
+    pub title: String,
+    /// proposal description
+    pub description: String,
+    /// optional link to further information on the proposal
+    pub link: Option<String>,
     /// proposal
     pub proposal: Proposal, 
     /// ftoken staked
@@ -346,10 +1152,48 @@ pub struct PropInfo {
     pub stake_withdrawn: bool,
     /// outcome. If still in voting, `outcome` = `None`. If vote has been finalized, `outcome` = `VoteResult`
     pub outcome: Option<VoteResult>,
-    // /// has the proposal been executed? A redundancy since outcome = Some(_) -> executed = true
-    // pub executed: bool,
+    /// has the proposal been executed? Only proposals that passed can be executed
+    pub executed: bool,
     /// block height where voting period ends. Final count tx can be called at this point forward
     pub end_height: u64,
+    /// block height when the proposal was made. Votes are weighted by voting power at this height
+    pub snapshot_height: u64,
+    /// secret ballot setting when the proposal was made. If true, the tally and individual votes
+    /// are hidden until the proposal is finalized
+    pub private_votes: bool,
+    /// vote scheme when the proposal was made
+    pub vote_scheme: VoteScheme,
+    /// slashing destination of the proposal stake, if vetoed, when the proposal was made
+    pub slash_dest: SlashDest,
+}
+
+impl PropInfo {
+    /// true if tally and individual votes cannot be revealed. Votes on a cancelled proposal 
+    /// stay hidden, as the proposal was never finalized
+    pub fn votes_hidden(&self) -> bool {
+        self.private_votes && matches!(self.outcome, None | Some(VoteResult::Cancelled))
+    }
+
+    /// proposals that have not been finalized when the vault closes are cancelled. This is applied
+    /// when a proposal is loaded, rather than to every pending proposal at closing. The stake of a
+    /// proposal cancelled this way is refunded later, so `stake_withdrawn` stays false until then
+    pub fn cancel_if_vault_closed(&mut self, vault_active: bool) {
+        if !vault_active && self.outcome.is_none() {
+            self.outcome = Some(VoteResult::Cancelled);
+        }
+    }
+
+    /// true if the proposal has the given status
+    pub fn has_status(&self, status: &PropStatus) -> bool {
+        match status {
+            PropStatus::Active => self.outcome.is_none(),
+            PropStatus::Passed => self.outcome == Some(VoteResult::Won),
+            PropStatus::Executed => self.executed,
+            PropStatus::Lost => self.outcome == Some(VoteResult::Lost),
+            PropStatus::Vetoed => self.outcome == Some(VoteResult::LostWithVeto),
+            PropStatus::Cancelled => self.outcome == Some(VoteResult::Cancelled),
+        }
+    }
 }
 
 // /// Proposal status
@@ -411,6 +1255,110 @@ impl BidInfo {
     }
 }
 
+/// Offer to buy up to `max_amount` ftokens at a fixed price. The offeror escrows bid tokens for the 
+/// full amount, ftoken holders who tender are paid immediately, and tendered ftokens are held by 
+/// the contract until the offer is settled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TenderOffer {
+    pub offer_id: u32,
+    pub offeror: HumanAddr,
+    /// token that the offer is paid in, which is the bid token when the offer was made
+    pub token: ContractInfo,
+    /// bid tokens paid per ftoken, both in smallest denomination
+    pub price: Uint128,
+    /// max number of ftokens the offeror will buy
+    pub max_amount: Uint128,
+    /// ftokens tendered so far
+    pub tendered: Uint128,
+    /// holders can tender until this block height
+    pub end_height: u64,
+    /// true once tendered ftokens are delivered to the offeror and unspent escrow is refunded
+    pub settled: bool,
+}
+
+impl TenderOffer {
+    /// bid tokens escrowed for the full offer
+    pub fn escrow(&self) -> Uint128 {
+        Uint128(self.price.u128() * self.max_amount.u128())
+    }
+
+    /// ftokens that can still be tendered
+    pub fn remaining(&self) -> Uint128 {
+        Uint128(self.max_amount.u128().saturating_sub(self.tendered.u128()))
+    }
+}
+
+/// Offer to buy the underlying NFT, which ftoken holders can accept through an `AcceptOffer` proposal. 
+/// The offer amount is escrowed by the contract until the offer is accepted or withdrawn
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BuyoutOffer {
+    pub offer_id: u32,
+    pub buyer: HumanAddr,
+    /// token that the offer is paid in, which is the bid token when the offer was made
+    pub token: ContractInfo,
+    pub amount: Uint128,
+    /// the offer is binding until this block height
+    pub end_height: u64,
+    /// latest proposal to accept the offer
+    pub prop_id: Option<u32>,
+    pub accepted: bool,
+    /// true once the buyer has withdrawn the escrowed offer amount
+    pub withdrawn: bool,
+}
+
+/// Proceeds from the sale of the underlying NFT, which ftoken holders can claim pro-rata
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SaleProceeds {
+    pub token: ContractInfo,
+    pub amount: Uint128,
+    /// ftokens burned to claim proceeds
+    pub claimed_ftokens: Uint128,
+    /// proceeds paid out to claimants
+    pub claimed: Uint128,
+}
+
+/// Sale proceeds claimed by an address, and the ftokens burned to claim them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ProceedsClaim {
+    pub ftokens: Uint128,
+    pub proceeds: Uint128,
+}
+
+/// Reservation price, and the stake that voted on it, from a given block height
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ResvCheckpoint {
+    pub height: u64,
+    pub price: Uint128,
+    pub stake: Uint128,
+}
+
+/// Reservation price vote cast by `voter` at block `height`. The vote expires at `height` plus the 
+/// vote lifetime, unless the voter has voted again since
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ResvVoteEntry {
+    pub voter: HumanAddr,
+    pub height: u64,
+}
+
+/// Stake voting for a reservation price, and the neighbouring price levels that have votes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ResvLevel {
+    pub stake: Uint128,
+    pub prev: Option<Uint128>,
+    pub next: Option<Uint128>,
+}
+
+/// Stake-weighted median of reservation price votes. The median price level is the lowest price
+/// at which at least half of the voting stake votes that price or lower
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ResvMedian {
+    /// median price level. `None` if there are no votes
+    pub median: Option<Uint128>,
+    /// stake voting below the median price
+    pub below: Uint128,
+    /// stake voting above the median price
+    pub above: Uint128,
+}
 
 /// Reservation price and stake stored in binary (serialized U192) 
 /// representing a Uint128 with additional 19 decimal points
@@ -14,12 +14,12 @@ use cosmwasm_std::{
 
 // ftoken additions:
 use fsnft_utils::{FtokenContrInit, FtokenInfo, FtokenConf};
-// use crate::{
-//     receiver::Snip20ReceiveMsg,
-// };
+use crate::{
+    receiver::Snip20ReceiveMsg,
+};
 use crate::ftoken_mod::{
     msg::{Proposal, FtokenQuery, FtokenAuthQuery, FtokenQueryAnswer},
-    state::{Vote},
+    state::{Vote, VaultStatus},
 };
 
 // use crate::ftoken_mod::ft_permit::Permit;
@@ -263,20 +263,25 @@ pub enum HandleMsg {
         amount: Uint128
     },
 
-    // /// Receiver interface for sSCRT contract's `SendFrom` callback
-    // Receive(Snip20ReceiveMsg),
+    /// Receiver interface for SNIP20 contracts' `Send` and `SendFrom` callbacks. Tokens 
+    /// registered with the vault treasury are added to the treasury
+    Receive(Snip20ReceiveMsg),
     /// Stake ftokens. Ftoken holders need to stake ftokens before voting on either
-    /// proposals or the reservation price. Staked ftokens will be bonded for a period
+    /// proposals or the reservation price. Unstaked ftokens will be bonded for a period
     /// specified by the configuration 
     Stake {
         /// The amount that the ftoken holder wishes to stake
         amount: Uint128,
     },
-    /// Unstake ftokens. Ftoken holders can unstake ftokens after the bonding period
+    /// Unstake ftokens. Unstaked ftokens stop counting towards votes immediately, and are 
+    /// held in an unbonding entry until the bonding period ends, or until the votes that bond
+    /// them end, whichever is later. An address can have several unbonding entries at a time
     Unstake {
         /// The amount that the ftoken holder wishes to unstake
         amount: Uint128,
     },
+    /// Returns ftokens from all unbonding entries that have matured
+    ClaimUnbonded { },
     /// Votes for DAO proposals to change configuration of the ftokens or send messages
     /// to the underlying NFT
     VoteProposal {
@@ -294,11 +299,28 @@ pub enum HandleMsg {
     /// If a bidder fails to win an auction, the bidder can retrieve its bid after the 
     /// auction period if over
     RetrieveBid { },
+    /// Once the vault has closed, refunds up to `limit` losing bids that have not been retrieved. 
+    /// Each call resumes from where the last one stopped. Anyone can call this
+    RefundLosingBids {
+        limit: u32,
+    },
     /// Once an underlying NFT is bought out, ftoken holders can claim their pro-rata
     /// share of sales proceeds
     ClaimProceeds { },
+    /// Once an underlying NFT is bought out, pushes pro-rata sale proceeds to up to `limit` 
+    /// ftoken holders that have not claimed them yet. Each call resumes from where the last 
+    /// one stopped. Anyone can call this
+    DistributeProceeds {
+        limit: u32,
+    },
     /// Make a DAO proposal
     Propose {
+        /// proposal title, up to 64 characters
+        title: String,
+        /// proposal description, up to 1024 characters
+        description: String,
+        /// optional link to further information, up to 256 characters
+        link: Option<String>,
         proposal: Proposal,
         /// Users need to stake a certain number of ftokens when making proposals, which 
         /// is set initially and can be later configured through the DAO. This stake can
@@ -318,10 +340,80 @@ pub enum HandleMsg {
     },
     /// ftoken holders whic have staked ftokens may vote a reservation price for 
     /// the underlying NFT. The weighted average votes on reservation price will be 
-    /// the minimum bid amount that bidders need to make to buy out the underlying NFT
+    /// the minimum bid amount that bidders need to make to buy out the underlying NFT.
+    /// Votes can be cast at up to 200 distinct prices at a time
     VoteReservationPrice {
         resv_price: Uint128,
-    }
+    },
+    /// Removes up to `limit` expired reservation price votes from the reservation price. Votes 
+    /// expire after the auction config's `resv_vote_lifetime`. Expired votes are also removed 
+    /// when the reservation price is next voted on or bid against, but this can be used if there 
+    /// are too many to remove in that transaction. Anyone can call this
+    ExpireResvVotes {
+        limit: u32,
+    },
+    /// Delegates the voting weight of the sender's staked ftokens to other addresses, 
+    /// replacing any existing delegations. Omitting an address removes the corresponding 
+    /// delegation. Delegators can still vote directly on individual proposals, which 
+    /// overrides their delegate's vote for that proposal. Delegations can only be changed 
+    /// after the sender's staked ftokens are unbonded  
+    Delegate {
+        /// address that votes on proposals on behalf of the sender
+        proposals: Option<HumanAddr>,
+        /// address that votes on the reservation price on behalf of the sender
+        resv_price: Option<HumanAddr>,
+    },
+    /// Deposit ftokens into the vault treasury. Treasury ftokens can only be paid out through
+    /// a `TreasurySpend` proposal, and do not receive a share of sale proceeds
+    TreasuryDeposit {
+        amount: Uint128,
+    },
+    /// Claim a pro-rata share of the stake of a vetoed proposal, for addresses that voted `veto`, 
+    /// if the proposal's slashing destination is `VetoVoters`
+    ClaimSlashedStake {
+        prop_id: u32,
+    },
+    /// Proposers may cancel their proposal, and retrieve their staked ftokens, before any
+    /// votes are cast on it or within the cancellation period set by the proposal config
+    CancelProposal {
+        prop_id: u32,
+    },
+    /// Guardians set in the ftoken config may change the vault status, to pause auctions or DAO 
+    /// operations in an emergency. The vault status can also be changed through a `SetVaultStatus` proposal
+    SetVaultStatus {
+        status: VaultStatus,
+    },
+    /// Offer to buy up to `amount` ftokens at a fixed `price` per ftoken, for `period` blocks. The 
+    /// offeror needs to give the ftoken contract an allowance for the full offer (`amount` x `price`) 
+    /// in the bid token, which is escrowed until the offer is settled
+    MakeTenderOffer {
+        amount: Uint128,
+        /// bid tokens paid per ftoken, both in smallest denomination
+        price: Uint128,
+        period: u64,
+    },
+    /// Tender ftokens to an open tender offer, and receive payment in bid tokens immediately
+    Tender {
+        offer_id: u32,
+        amount: Uint128,
+    },
+    /// Once a tender offer expires or is fully tendered, anyone can call this to deliver the 
+    /// tendered ftokens to the offeror and refund the unspent escrow
+    SettleTenderOffer {
+        offer_id: u32,
+    },
+    /// Offer to buy the underlying NFT for `amount` bid tokens. The offeror needs to give the ftoken
+    /// contract an allowance for `amount` in the bid token, which is escrowed. The offer is binding
+    /// for `period` blocks, during which ftoken holders can accept it through an `AcceptOffer` proposal
+    MakeBuyoutOffer {
+        amount: Uint128,
+        period: u64,
+    },
+    /// Withdraw an escrowed buyout offer that has expired, was rejected, or can no longer be 
+    /// accepted because the vault has closed
+    WithdrawBuyoutOffer {
+        offer_id: u32,
+    },
 
 }
 
@@ -435,6 +527,9 @@ pub enum HandleAnswer {
     Unstake {
         status: ResponseStatus,
     },
+    ClaimUnbonded {
+        status: ResponseStatus,
+    },
     VoteProposal {
         status: ResponseStatus,
     },
@@ -444,9 +539,15 @@ pub enum HandleAnswer {
     RetrieveBid { 
         status: ResponseStatus,
     },
+    RefundLosingBids {
+        status: ResponseStatus,
+    },
     ClaimProceeds { 
         status: ResponseStatus,
     },
+    DistributeProceeds {
+        status: ResponseStatus,
+    },
     Propose {
         status: ResponseStatus,    
     },
@@ -458,7 +559,43 @@ pub enum HandleAnswer {
     },
     VoteReservationPrice {
         status: ResponseStatus,
-    }
+    },
+    ExpireResvVotes {
+        status: ResponseStatus,
+    },
+    Delegate {
+        status: ResponseStatus,
+    },
+    Receive {
+        status: ResponseStatus,
+    },
+    TreasuryDeposit {
+        status: ResponseStatus,
+    },
+    ClaimSlashedStake {
+        status: ResponseStatus,
+    },
+    CancelProposal {
+        status: ResponseStatus,
+    },
+    SetVaultStatus {
+        status: ResponseStatus,
+    },
+    MakeTenderOffer {
+        status: ResponseStatus,
+    },
+    Tender {
+        status: ResponseStatus,
+    },
+    SettleTenderOffer {
+        status: ResponseStatus,
+    },
+    MakeBuyoutOffer {
+        status: ResponseStatus,
+    },
+    WithdrawBuyoutOffer {
+        status: ResponseStatus,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -6,7 +6,7 @@ use cosmwasm_std::{
 };
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
 
-use secret_toolkit::storage::{TypedStore, TypedStoreMut};
+use secret_toolkit::storage::{AppendStore, AppendStoreMut, TypedStore, TypedStoreMut};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -33,6 +33,9 @@ pub const PREFIX_BALANCES: &[u8] = b"ftbalances";
 pub const PREFIX_ALLOWANCES: &[u8] = b"ftallowances";
 pub const PREFIX_VIEW_KEY: &[u8] = b"ftviewingkey";
 pub const PREFIX_RECEIVERS: &[u8] = b"ftreceivers";
+// ftoken additions
+pub const PREFIX_HOLDERS: &[u8] = b"ftholders";
+pub const PREFIX_HOLDER_INDEX: &[u8] = b"ftholderindex";
 
 
 // Config
@@ -377,6 +380,36 @@ pub fn set_receiver_hash<S: Storage>(store: &mut S, account: &HumanAddr, code_ha
 }
 
 
+// Holders (ftoken addition)
+
+/// Adds an account to the index of addresses that have held ftokens, if not already in it. 
+/// Accounts stay in the index after their balance falls to zero
+pub fn index_holder<S: Storage>(store: &mut S, account: &CanonicalAddr) -> StdResult<()> {
+    let mut index_store = PrefixedStorage::new(PREFIX_HOLDER_INDEX, store);
+    if index_store.get(account.as_slice()).is_some() {
+        return Ok(());
+    }
+    index_store.set(account.as_slice(), &[1]);
+
+    let mut store = PrefixedStorage::new(PREFIX_HOLDERS, store);
+    let mut store = AppendStoreMut::<CanonicalAddr, _>::attach_or_create(&mut store)?;
+    store.push(account)
+}
+
+/// Returns the holder at position `pos` of the holder index, if any
+pub fn may_get_holder<S: ReadonlyStorage>(store: &S, pos: u32) -> StdResult<Option<CanonicalAddr>> {
+    let store = ReadonlyPrefixedStorage::new(PREFIX_HOLDERS, store);
+    let store = match AppendStore::<CanonicalAddr, _, _>::attach(&store) {
+        Some(result) => result?,
+        None => return Ok(None),
+    };
+    match pos < store.len() {
+        true => Ok(Some(store.get_at(pos)?)),
+        false => Ok(None),
+    }
+}
+
+
 // Helpers
 
 /// Converts 16 bytes value into u128
@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use cosmwasm_std::{
-    HumanAddr, Uint128, Storage, StdResult, CosmosMsg,
+    HumanAddr, Uint128, Storage, StdError, StdResult, CosmosMsg,
     Binary, Api, Querier, Extern, Env,
 };
 // use cosmwasm_std::testing::{mock_env};  // mock_dependencies, MockStorage, MockApi, MockQuerier,
@@ -46,6 +46,13 @@ pub enum InterContrMsg{
         /// optional message to send with the (Batch)RecieveNft callback
         msg: Option<Binary>,
     },
+    /// `RegisterReceive` message to send to SNIP20 token address
+    RegisterReceive {
+        /// receving contract's code hash
+        code_hash: String,
+        /// optional message length padding
+        padding: Option<String>,
+    },
     /// `Send` message to send to SNIP20 token address
     Send {
         recipient: HumanAddr,
@@ -107,7 +114,8 @@ impl HandleCallback for InterContrMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
 pub struct FtokenConf {
     /// Number of blocks that ftokens will be bonded after a vote (on reservation
-    /// price or on proposals). Important to prevent vote spamming and manipulation 
+    /// price or on proposals), and that unstaked ftokens are unbonding for. Important 
+    /// to prevent vote spamming and manipulation 
     pub min_ftkn_bond_prd: u64,
     /// Proportion of ftoken ownership required before private metadata of underlying
     /// NFT can be queried by ftoken owner. This needs to be done with authenticated
@@ -118,6 +126,9 @@ pub struct FtokenConf {
     pub auc_conf: AucConf,
     /// Configurations for proposals
     pub prop_conf: PropConf,
+    /// Addresses that can change the vault status (eg: to pause auctions or DAO operations in an
+    /// emergency) without a proposal. Can be changed through a `ChangeConfig` proposal
+    pub guardians: Vec<HumanAddr>,
 }
 
 /// ftoken config for bidding. Nested in a larger struct
@@ -137,10 +148,29 @@ pub struct AucConf {
     pub min_bid_inc: u32,
     /// Proportion of ftoken OF TOTAL SUPPLY before NFT gets unlocked. Unit in basis points (1/1000)
     pub unlock_threshold: Uint128,
+    /// Method used to aggregate reservation price votes
+    pub resv_agg: ResvAgg,
+    /// Number of blocks that a reservation price vote counts towards the reservation price, after which 
+    /// the vote expires unless it is cast again. A setting of 0 means that votes do not expire
+    pub resv_vote_lifetime: u64,
+    /// If true, the previous highest bidder is refunded as soon as they are outbid. Otherwise, losing 
+    /// bidders retrieve their bids after the auction has ended
+    pub refund_outbid: bool,
 }
 
-/// ftoken contract config for dao proposals. Nested in a larger struct
+/// Aggregation of ftoken-weighted reservation price votes
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ResvAgg {
+    /// Stake-weighted mean of votes
+    #[default]
+    Mean,
+    /// Stake-weighted median of votes, which a single large holder cannot drag towards an extreme
+    Median,
+}
+
+/// ftoken contract config for dao proposals. Nested in a larger struct
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PropConf {
     /// Minimum ftoken stake to make a proposal
     pub min_stake: Uint128,
@@ -150,6 +180,218 @@ pub struct PropConf {
     pub vote_quorum: Uint128,
     /// Proportion of ftoken-weighted votes OF TOTAL SUPPLY that needs to vote `veto` for a veto to apply. Unit in basis points (1/1000)
     pub veto_threshold: Uint128,
+    /// Proportion of `yes` votes out of `yes` + `no` votes that needs to be exceeded for a proposal to pass. 
+    /// Unit in basis points (1/10_000). A setting of 5000 means a simple majority
+    pub pass_threshold: Uint128,
+    /// Overrides of `vote_quorum`, `pass_threshold` and `veto_threshold` for specific proposal types
+    pub threshold_overrides: Vec<ThresholdOverride>,
+    /// Proportion of ftoken-weighted `yes` votes OF TOTAL SUPPLY at which proposals to rotate the vault's viewing key,
+    /// revoke its permits on the underlying NFT, or change the vault status can be executed before the voting 
+    /// period ends. Unit in basis points (1/10_000)
+    pub emergency_threshold: Uint128,
+    /// Secret ballot: if true, the running tally and individual votes of a proposal cannot be queried 
+    /// until the proposal is finalized. Only the turnout is visible while voting is live
+    pub private_votes: bool,
+    /// How votes on proposals are weighted
+    pub vote_scheme: VoteScheme,
+    /// Destination of the ftoken stake of proposals that are vetoed
+    pub slash_dest: SlashDest,
+    /// Number of blocks after a proposal is made during which the proposer can cancel it. After 
+    /// this, the proposer can only cancel the proposal if no votes have been cast on it
+    pub cancel_period: u64,
+    /// Messages that can be proposed to be sent to the underlying NFT, and their vote thresholds. Messages 
+    /// not listed are disabled. If not given, the messages that were always allowed are enabled: 
+    /// `SetMetadata`, `Reveal`, `MakeOwnershipPrivate`, `SetGlobalApproval` and `SetWhitelistedApproval`
+    #[serde(default = "NftMsgPolicy::defaults")]
+    pub nft_msg_policies: Vec<NftMsgPolicy>,
+}
+
+/// Pass and emergency thresholds cannot be zero, so default to a simple majority and a two-thirds 
+/// supermajority respectively
+impl Default for PropConf {
+    fn default() -> Self {
+        PropConf {
+            min_stake: Uint128::default(),
+            vote_period: 0,
+            vote_quorum: Uint128::default(),
+            veto_threshold: Uint128::default(),
+            pass_threshold: Uint128(5000),
+            threshold_overrides: vec![],
+            emergency_threshold: Uint128(6667),
+            private_votes: false,
+            vote_scheme: VoteScheme::default(),
+            slash_dest: SlashDest::default(),
+            cancel_period: 0,
+            nft_msg_policies: NftMsgPolicy::defaults(),
+        }
+    }
+}
+
+impl PropConf {
+    /// checks that pass thresholds, including those of proposal type overrides and NFT message policies, and 
+    /// the emergency threshold are between 1 and 9999 basis points. A zero threshold lets a single `yes` vote 
+    /// pass a proposal, while a threshold of 10_000 or more means no proposal can pass
+    pub fn validate_thresholds(&self) -> StdResult<()> {
+        let check = |name: String, threshold: Uint128| match threshold.u128() {
+            1..=9999 => Ok(()),
+            _ => Err(StdError::generic_err(format!(
+                "{} must be between 1 and 9999 basis points, got {}", name, threshold
+            ))),
+        };
+        check("pass_threshold".to_string(), self.pass_threshold)?;
+        check("emergency_threshold".to_string(), self.emergency_threshold)?;
+        for over in &self.threshold_overrides {
+            if let Some(pass_threshold) = over.pass_threshold {
+                check(format!("pass_threshold override of {:?} proposals", over.prop_type), pass_threshold)?;
+            }
+        }
+        for policy in &self.nft_msg_policies {
+            if let Some(pass_threshold) = policy.pass_threshold {
+                check(format!("pass_threshold of {:?} NFT message policy", policy.msg_type), pass_threshold)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// vote thresholds that apply to a proposal type, after overrides
+    pub fn thresholds(&self, prop_type: &PropType) -> VoteThresholds {
+        let over = self.threshold_overrides.iter().find(|i| &i.prop_type == prop_type);
+        VoteThresholds {
+            prop_type: prop_type.clone(),
+            vote_quorum: over.and_then(|i| i.vote_quorum).unwrap_or(self.vote_quorum),
+            pass_threshold: over.and_then(|i| i.pass_threshold).unwrap_or(self.pass_threshold),
+            veto_threshold: over.and_then(|i| i.veto_threshold).unwrap_or(self.veto_threshold),
+        }
+    }
+
+    /// governance policy of a message to the underlying NFT. `None` if the message is disabled
+    pub fn nft_msg_policy(&self, msg_type: &NftMsgType) -> Option<&NftMsgPolicy> {
+        self.nft_msg_policies.iter().find(|i| &i.msg_type == msg_type)
+    }
+
+    /// vote thresholds that apply to a proposal to send a message to the underlying NFT. Thresholds
+    /// not set by the message's policy are those of `MsgToNft` proposals
+    pub fn nft_msg_thresholds(&self, msg_type: &NftMsgType) -> VoteThresholds {
+        let mut thresholds = self.thresholds(&PropType::MsgToNft);
+        if let Some(policy) = self.nft_msg_policy(msg_type) {
+            thresholds.vote_quorum = policy.vote_quorum.unwrap_or(thresholds.vote_quorum);
+            thresholds.pass_threshold = policy.pass_threshold.unwrap_or(thresholds.pass_threshold);
+            thresholds.veto_threshold = policy.veto_threshold.unwrap_or(thresholds.veto_threshold);
+        }
+        thresholds
+    }
+}
+
+/// Types of messages that can be sent to the underlying NFT, corresponding to the ftoken contract's 
+/// `AllowedNftMsg` variants
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NftMsgType {
+    SetMetadata,
+    SetRoyaltyInfo,
+    Reveal,
+    MakeOwnershipPrivate,
+    SetGlobalApproval,
+    SetWhitelistedApproval,
+    Revoke,
+    RevokeAll,
+    RevokePermit,
+}
+
+/// Enables a message to the underlying NFT. Thresholds that are `None` use the thresholds of `MsgToNft` 
+/// proposals
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftMsgPolicy {
+    pub msg_type: NftMsgType,
+    pub vote_quorum: Option<Uint128>,
+    pub pass_threshold: Option<Uint128>,
+    pub veto_threshold: Option<Uint128>,
+}
+
+impl NftMsgPolicy {
+    /// policies enabling the messages that could always be sent to the underlying NFT, with the 
+    /// thresholds of `MsgToNft` proposals
+    pub fn defaults() -> Vec<NftMsgPolicy> {
+        vec![
+            NftMsgType::SetMetadata, NftMsgType::Reveal, NftMsgType::MakeOwnershipPrivate,
+            NftMsgType::SetGlobalApproval, NftMsgType::SetWhitelistedApproval,
+        ].into_iter().map(|msg_type| NftMsgPolicy { 
+            msg_type, vote_quorum: None, pass_threshold: None, veto_threshold: None,
+        }).collect()
+    }
+}
+
+/// Types of DAO proposals, corresponding to the ftoken contract's `Proposal` variants
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PropType {
+    MsgToNft,
+    ChangeConfig,
+    RegisterTreasuryToken,
+    TreasurySpend,
+    RotateViewingKey,
+    RevokePermits,
+    SetVaultStatus,
+    AcceptOffer,
+}
+
+impl PropType {
+    pub fn all() -> Vec<PropType> {
+        vec![
+            PropType::MsgToNft, PropType::ChangeConfig, PropType::RegisterTreasuryToken, PropType::TreasurySpend,
+            PropType::RotateViewingKey, PropType::RevokePermits, PropType::SetVaultStatus,
+            PropType::AcceptOffer,
+        ]
+    }
+}
+
+/// Vote thresholds for a proposal type. Thresholds that are `None` use the default set in `PropConf`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ThresholdOverride {
+    pub prop_type: PropType,
+    pub vote_quorum: Option<Uint128>,
+    pub pass_threshold: Option<Uint128>,
+    pub veto_threshold: Option<Uint128>,
+}
+
+/// Vote thresholds that apply to a proposal type. Units in basis points (1/10_000)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteThresholds {
+    pub prop_type: PropType,
+    pub vote_quorum: Uint128,
+    pub pass_threshold: Uint128,
+    pub veto_threshold: Uint128,
+}
+
+/// Destination of proposal stakes slashed when a proposal is vetoed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SlashDest {
+    /// Burn the stake, reducing total supply
+    #[default]
+    Burn,
+    /// Add the stake to the vault treasury
+    Treasury,
+    /// Distribute the stake to the addresses that voted `veto`, pro rata to their voting power. Ftokens
+    /// delegated to a veto voter are credited to the delegators
+    VetoVoters,
+}
+
+/// Weighting of ftoken-weighted votes on proposals. The vote scheme weights `yes` against `no` votes for the 
+/// pass threshold. Quorum, veto and emergency thresholds are proportions of the total supply, so are measured
+/// with voting power regardless of the vote scheme
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteScheme {
+    /// Vote weight equals voting power
+    #[default]
+    Linear,
+    /// Vote weight is the square root of voting power
+    Quadratic,
+    /// Vote weight grows linearly with the number of blocks voting power has been committed to the vote 
+    /// before the voting period ends: from 1x for votes cast at the end, to `max_multiplier`x for votes
+    /// cast when the proposal is made
+    Conviction { max_multiplier: u8 },
 }
 
 /// ftoken contract information, stored in ftoken contracts
@@ -22,7 +22,8 @@ use fractionalizer as frc;
 use ftoken as ft;
 use ftoken::{
     ftoken_mod::{
-        state::{agg_resv_price_w, agg_resv_price_r, ResvVote}
+        state::{agg_resv_price_w, agg_resv_price_r, ftkn_config_r, prop_id_r, ResvVote, Vote},
+        msg::{FtokenQuery, FtokenQueryAnswer, Proposal},
     }
 };
 
@@ -32,7 +33,7 @@ use snip721_reference_impl as s721;
 use snip20_reference_impl as s20;
 
 use fsnft_utils::{
-    UndrNftInfo, ContractInfo, FtokenInit, FtokenConf, AucConf, PropConf
+    UndrNftInfo, ContractInfo, FtokenInit, FtokenConf, AucConf, PropConf, VoteScheme, SlashDest, NftMsgPolicy, ResvAgg,
 }; 
 
 
@@ -205,6 +206,15 @@ pub(crate) fn s20_balance(app: &mut App, addr_key: &str) -> Uint128 {
     ))
 }
 
+/// performs a public ftoken query and unwraps the `FtokenQueryAnswer`
+pub(crate) fn ftoken_query(app: &App, query: FtokenQuery) -> StdResult<FtokenQueryAnswer> {
+    let bin = ft::contract::query(&app.deps, ft::msg::QueryMsg::FtokenQuery(query))?;
+    match from_binary(&bin)? {
+        ft::msg::QueryAnswer::FtokenQueryAnswer(answer) => Ok(answer),
+        _ => Err(StdError::generic_err("unexpected query answer")),
+    }
+}
+
 
 /////////////////////////////////////////////////////////////////////////////////
 // pub functions: helpers
@@ -231,6 +241,46 @@ pub(crate) fn init_default(app: &mut App) {
 }
 
 pub(crate) fn fractionalize_default(app: &mut App) {
+    let ftkn_conf = default_ftkn_conf(app);
+    fractionalize_with_conf(app, ftkn_conf);
+}
+
+/// ftoken config used by `fractionalize_default`. Tests that need a different config modify this and 
+/// fractionalize with `fractionalize_with_conf`
+pub(crate) fn default_ftkn_conf(app: &App) -> FtokenConf {
+    FtokenConf {
+        min_ftkn_bond_prd: 10u64,
+        priv_metadata_view_threshold: 5_000,
+        auc_conf: AucConf {
+            bid_token: app.get_addr("s20"),
+            auc_period: 100,
+            resv_boundary: 500,
+            min_bid_inc: 1000u32,
+            unlock_threshold: Uint128(5_000),
+            resv_agg: ResvAgg::Mean,
+            resv_vote_lifetime: 0,
+            refund_outbid: false,
+        },
+        prop_conf: PropConf { 
+            min_stake: Uint128(2),
+            vote_period: 200, 
+            vote_quorum: Uint128(2000), 
+            veto_threshold: Uint128(1000), 
+            pass_threshold: Uint128(5000),
+            threshold_overrides: vec![],
+            emergency_threshold: Uint128(6667),
+            private_votes: false,
+            vote_scheme: VoteScheme::Linear,
+            slash_dest: SlashDest::Burn,
+            cancel_period: 10u64,
+            nft_msg_policies: NftMsgPolicy::defaults(),
+        },
+        guardians: vec![],
+    }
+}
+
+/// fractionalizes "MyNFT" with the default ftoken init variables and the given ftoken config
+pub(crate) fn fractionalize_with_conf(app: &mut App, ftkn_conf: FtokenConf) {
     // set default variables
     let nft_info = UndrNftInfo {
         token_id: "MyNFT".to_string(),
@@ -245,23 +295,7 @@ pub(crate) fn fractionalize_default(app: &mut App) {
             decimals: 6u8,
             contract_label: "myftokenlabel".to_string(),
             init_resv_price: Uint128(500),
-            ftkn_conf: FtokenConf {
-                min_ftkn_bond_prd: 10u64,
-                priv_metadata_view_threshold: 5_000,
-                auc_conf: AucConf {
-                    bid_token: app.get_addr("s20"),
-                    auc_period: 100,
-                    resv_boundary: 500,
-                    min_bid_inc: 1000u32,
-                    unlock_threshold: Uint128(5_000),
-                },
-                prop_conf: PropConf { 
-                    min_stake: Uint128(2),
-                    vote_period: 200, 
-                    vote_quorum: Uint128(2000), 
-                    veto_threshold: Uint128(1000), 
-                },
-            },
+            ftkn_conf,
         },
     };
 
@@ -272,6 +306,58 @@ pub(crate) fn fractionalize_default(app: &mut App) {
 }
 
 
+/// `proposer` makes a proposal, staking `stake` ftokens
+pub(crate) fn propose(
+    app: &mut App,
+    proposer: &str,
+    proposal: Proposal,
+    stake: u128,
+) -> StdResult<HandleResponse> {
+    app.change_env(proposer, "ft");
+    let msg = ft::msg::HandleMsg::Propose { 
+        title: "proposal".to_string(), 
+        description: String::new(), 
+        link: None, 
+        proposal, 
+        stake: Uint128(stake),
+    };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg)
+}
+
+/// `voter` votes on proposal `prop_id`
+pub(crate) fn vote_proposal(
+    app: &mut App,
+    voter: &str,
+    prop_id: u32,
+    vote: Vote,
+) -> StdResult<HandleResponse> {
+    app.change_env(voter, "ft");
+    let msg = ft::msg::HandleMsg::VoteProposal { prop_id, vote };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg)
+}
+
+/// `voter` proposes a change to the ftoken config, votes for it, and finalizes it at the end of the 
+/// voting period, then retrieves the proposal stake. `voter` needs enough staked ftokens to pass the 
+/// proposal alone. Moves the block height forward by the vote period
+pub(crate) fn change_config(
+    app: &mut App,
+    voter: &str,
+    config: FtokenConf,
+) -> StdResult<()> {
+    let prop_id = prop_id_r(&app.deps.storage).load()?;
+    let vote_period = ftkn_config_r(&app.deps.storage).load()?.prop_conf.vote_period;
+    propose(app, voter, Proposal::ChangeConfig { config }, 2)?;
+    vote_proposal(app, voter, prop_id, Vote::Yes)?;
+
+    app.next_block(vote_period);
+    let msg = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg)?;
+    let msg = ft::msg::HandleMsg::RetrievePropStake { prop_id };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg)?;
+
+    Ok(())
+}
+
 /// user0 transfers N ftokens to user1 after fractionalizing. Then both stake a certain amount in ftoken contract
 /// 
 /// # Arguments
@@ -334,8 +420,8 @@ pub(crate) fn sim_fractionalize(
     let msg = extract_cmsg_check_env::<ft::msg::InitMsg>(&app, &handle_resp.messages[1]).unwrap();
     let ft_init_resp = ft::contract::init(&mut app.deps, app.env.clone(), msg).unwrap();
 
-    // check there are two messages in the response
-    assert_eq!(ft_init_resp.messages.len(), 2);
+    // check there are three messages in the response
+    assert_eq!(ft_init_resp.messages.len(), 3);
 
     // message0: contract-to-contract call ftoken init response -> fractionalizer handle ---
     app.change_env("ft", "frc");
@@ -348,6 +434,12 @@ pub(crate) fn sim_fractionalize(
     // message1: ftoken init response -> SNIP721 SetViewingKey  --------------------
     // todo!()
 
+    // message2: ftoken init response -> SNIP20 (bid token) RegisterReceive --------
+    app.change_env("ft", "s20");
+    let msg = extract_cmsg_check_env::<s20::msg::HandleMsg>(app, &ft_init_resp.messages[2]).unwrap();
+    let handle_resp_2 = s20::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    assert_eq!(handle_resp_2.messages.len(), 0);
+
 
     // fractionalizer -> SNIP721 `SendNft` handle ----------------------------------
     app.change_env("frc", "s721");
@@ -409,13 +501,16 @@ pub(crate) fn sim_bid(
     app.change_env(sender, "ft");
     let msg = ft::msg::HandleMsg::Bid { amount: Uint128(amount) };
     let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg)?;
-    assert_eq!(handle_resp.messages.len(), 1);
+    assert!(!handle_resp.messages.is_empty());
 
     // message0: ftoken contract -> `TransferFrom` to snip20 contract
+    // message1 (if any): ftoken contract -> `Transfer` to snip20 contract, refunding the outbid bidder
     app.change_env("ft", "s20");
-    let msg = extract_cmsg_check_env::<s20::msg::HandleMsg>(&app, &handle_resp.messages[0]).unwrap();
-    let handle_resp_0 =s20::contract::handle(&mut app.deps, app.env.clone(), msg)?;
-    assert_eq!(handle_resp_0.messages.len(), 0);
+    for message in handle_resp.messages.iter() {
+        let msg = extract_cmsg_check_env::<s20::msg::HandleMsg>(&app, message).unwrap();
+        let handle_resp_0 =s20::contract::handle(&mut app.deps, app.env.clone(), msg)?;
+        assert_eq!(handle_resp_0.messages.len(), 0);
+    }
 
     // revert to previous environment
     app.env = prev_env;
@@ -475,6 +570,60 @@ pub(crate) fn sim_retrieve_bid(
     Ok(HandleResponse::default())
 }
 
+/// Simulates a SNIP20 (sSCRT) `Send` to the ftoken contract, with the `Receive` callback
+pub(crate) fn sim_send_s20_to_ft(
+    app: &mut App,
+    sender: &str,
+    amount: u128,
+) -> StdResult<HandleResponse> {
+    // save current environment, to revert back at the end
+    let prev_env = app.env.clone();
+
+    app.change_env(sender, "s20");
+    let msg = s20::msg::HandleMsg::Send { 
+        recipient: app.get_addr("ft").address, 
+        recipient_code_hash: None, 
+        amount: Uint128(amount), 
+        msg: None, 
+        memo: None, 
+        padding: None,
+    };
+    let handle_resp = s20::contract::handle(&mut app.deps, app.env.clone(), msg)?;
+    assert_eq!(handle_resp.messages.len(), 1);
+
+    // message0: snip20 contract -> `Receive` to ftoken contract
+    app.change_env("s20", "ft");
+    let msg = extract_cmsg_check_env::<ft::msg::HandleMsg>(&app, &handle_resp.messages[0]).unwrap();
+    let handle_resp_0 = ft::contract::handle(&mut app.deps, app.env.clone(), msg)?;
+
+    // revert to previous environment
+    app.env = prev_env;
+
+    Ok(handle_resp_0)
+}
+
+/// Simulates the `Transfer` and `TransferFrom` messages that the ftoken contract sends to the
+/// SNIP20 (sSCRT) contract in a handle response
+pub(crate) fn sim_ft_msgs_to_s20(
+    app: &mut App,
+    handle_resp: &HandleResponse,
+) -> StdResult<()> {
+    // save current environment, to revert back at the end
+    let prev_env = app.env.clone();
+
+    app.change_env("ft", "s20");
+    for message in handle_resp.messages.iter() {
+        let msg = extract_cmsg_check_env::<s20::msg::HandleMsg>(app, message)?;
+        let handle_resp = s20::contract::handle(&mut app.deps, app.env.clone(), msg)?;
+        assert_eq!(handle_resp.messages.len(), 0);
+    }
+
+    // revert to previous environment
+    app.env = prev_env;
+
+    Ok(())
+}
+
 /// simulates `RetrieveNft` function on ftoken contract
 // pub(crate) fn sim_retrieve_nft(
 //     app: &mut App,
@@ -507,13 +656,15 @@ pub(crate) fn sim_claim_proceeds(
     app.change_env(sender, "ft");
     let msg = ft::msg::HandleMsg::ClaimProceeds { };
     let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg)?;
-    assert_eq!(handle_resp.messages.len(), 1);
 
     // message0: ftoken contract -> `Transfer` to snip20 contract
+    // message1..: ftoken contract -> `Transfer` to snip20 contract, for treasury balances 
     app.change_env("ft", "s20");
-    let msg = extract_cmsg_check_env::<s20::msg::HandleMsg>(&app, &handle_resp.messages[0]).unwrap();
-    let handle_resp =s20::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
-    assert_eq!(handle_resp.messages.len(), 0);
+    for message in handle_resp.messages.iter() {
+        let msg = extract_cmsg_check_env::<s20::msg::HandleMsg>(app, message).unwrap();
+        let handle_resp =s20::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+        assert_eq!(handle_resp.messages.len(), 0);
+    }
 
     // revert to previous environment
     app.env = prev_env;
@@ -544,7 +695,7 @@ pub(crate) fn sim_claim_proceeds(
 /// app.change_env("user0", "ft");
 /// let msg = extract_cmsg_check_env::<HandleMsg>(&app, &message).unwrap();
 /// ```
-fn extract_cmsg_check_env<U: DeserializeOwned>(app: &App, message: &CosmosMsg) -> StdResult<U> {
+pub(crate) fn extract_cmsg_check_env<U: DeserializeOwned>(app: &App, message: &CosmosMsg) -> StdResult<U> {
     let (msg, addr_op, hash) = extract_cosmos_msg(&message).unwrap();
 
     // If `None` means the message is `WasmMsg::Instantiate`
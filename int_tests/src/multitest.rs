@@ -1,5 +1,5 @@
 use cosmwasm_std::{
-    Uint128, to_binary, 
+    Uint128, to_binary, from_binary, from_slice, log,
     Api,
 };
 
@@ -27,16 +27,18 @@ use snip721_reference_impl::{
     }
 };
 
-// use snip20_reference_impl as s20;
+use snip20_reference_impl as s20;
 
 use fsnft_utils::{
-    UndrNftInfo, FtokenInfo, FtokenInstance, AucConf, // FtokenInit, FtokenConf, AucConf, PropConf,
+    UndrNftInfo, FtokenInfo, FtokenInstance, AucConf, VoteScheme, SlashDest, PropType, ThresholdOverride, VoteThresholds, NftMsgType, NftMsgPolicy, PropConf, ResvAgg, // FtokenInit, FtokenConf, AucConf,
 };
 
 use crate::helpers::{
     App, extract_error_msg,
     init_default, fractionalize_default, ftoken_balance, s20_balance, transfer_ftkn_and_stake, sim_bid, 
-    sim_finalize_auction, sim_retrieve_bid, sim_claim_proceeds,
+    sim_finalize_auction, sim_retrieve_bid, sim_claim_proceeds, ftoken_query, propose, vote_proposal,
+    sim_send_s20_to_ft, extract_cmsg_check_env, sim_ft_msgs_to_s20,
+    default_ftkn_conf, fractionalize_with_conf, change_config,
 };
 
 
@@ -137,58 +139,81 @@ fn test_stake_unstake() {
     let _handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
     let mut exp_staked = StakedTokens {
         amount: Uint128(100),
-        unlock_height: 10u64,
+        unlock_height: 0u64,
     };
     let act_staked = ftkn_stake_r(&app.deps.storage).load(
         &to_binary(&app.get_addr("user0").address).unwrap().as_slice()
     ).unwrap();
-    // check 100 ftoken staked, bonded for 10 blocks 
+    // check 100 ftoken staked, not bonded until unstaked 
     assert_eq!(act_staked, exp_staked);  
     // check depositor has 0 ftokens
     let depositor_bal = ftoken_balance(&mut app, "user0");
     assert_eq!(depositor_bal, Uint128(0));
 
-    app.next_block(5);
-    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(100) };
-    let handle_result = ft::contract::handle(&mut app.deps, app.env.clone(), msg);
-    let error = extract_error_msg(handle_result);
-    assert!(error.contains("ftokens are still bonded. Will unbond at height 10"));
-
     app.next_block(5);
     let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(500) };
     let handle_result = ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone());
     let error = extract_error_msg(handle_result);
-    assert!(error.contains("insufficient funds"));
+    assert!(error.contains("insufficient staked ftokens"));
 
     app.change_env("user1", "ft");
     let handle_result = ft::contract::handle(&mut app.deps, app.env.clone(), msg);
     let error = extract_error_msg(handle_result);
     assert!(error.contains("this address has not staked ftokens before"));
 
-    // unstake partially should work
+    // unstake partially should work, and ftokens unbond for 10 blocks
     app.change_env("user0", "ft");
     let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(20) };
-    let _handle_result = ft::contract::handle(&mut app.deps, app.env.clone(), msg);
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
     exp_staked.amount = Uint128(80);
     let act_staked = ftkn_stake_r(&app.deps.storage).load(
         &to_binary(&app.get_addr("user0").address).unwrap().as_slice()
     ).unwrap();
     // check 80 ftoken staked now
     assert_eq!(act_staked, exp_staked);  
-    // check depositor has 20 ftokens
+    // check depositor has no ftokens until unbonded
+    let depositor_bal = ftoken_balance(&mut app, "user0");
+    assert_eq!(depositor_bal, Uint128(0));
+    let msg = ft::msg::HandleMsg::ClaimUnbonded {  };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("no unbonding ftokens have matured"));
+
+    // several unbonding entries can coexist
+    app.next_block(5);
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(30) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let unbonding = unbonding_r(&app.deps.storage).load(
+        &to_binary(&app.get_addr("user0").address).unwrap().as_slice()
+    ).unwrap();
+    assert_eq!(unbonding, vec![
+        UnbondingEntry { amount: Uint128(20), mature_height: 15 },
+        UnbondingEntry { amount: Uint128(30), mature_height: 20 },
+    ]);
+
+    // claiming only returns matured entries
+    app.next_block(5);
+    let msg = ft::msg::HandleMsg::ClaimUnbonded {  };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
     let depositor_bal = ftoken_balance(&mut app, "user0");
     assert_eq!(depositor_bal, Uint128(20));
+    let unbonding = unbonding_r(&app.deps.storage).load(
+        &to_binary(&app.get_addr("user0").address).unwrap().as_slice()
+    ).unwrap();
+    assert_eq!(unbonding, vec![UnbondingEntry { amount: Uint128(30), mature_height: 20 }]);
 
     // unstake remaining ftokens after a while, should work
     app.next_block(100);
-    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(80) };
-    let _handle_result = ft::contract::handle(&mut app.deps, app.env.clone(), msg);
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(50) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
     exp_staked.amount = Uint128(0);
     let act_staked = ftkn_stake_r(&app.deps.storage).load(
         &to_binary(&app.get_addr("user0").address).unwrap().as_slice()
     ).unwrap();
     // check 0 ftoken staked now
     assert_eq!(act_staked, exp_staked);  
+    app.next_block(10);
+    let msg = ft::msg::HandleMsg::ClaimUnbonded {  };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
     // check depositor has all 100 ftokens back
     let depositor_bal = ftoken_balance(&mut app, "user0");
     assert_eq!(depositor_bal, Uint128(100));
@@ -248,16 +273,16 @@ fn test_auction_process() {
     agg_resv_price = agg_resv_price_r(&app.deps.storage).load().unwrap();
     assert_eq!(agg_resv_price.uint128_price(), Uint128(65));
 
-    // unstake tx changes reservation price, does not increase bonding period
-    app.next_block(5);
+    // unstake tx changes reservation price immediately, while the ftokens unbond
+    app.next_block(10);
     msg = ft::msg::HandleMsg::Unstake { amount: Uint128(40) };
-    error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()));
-    assert!(error.contains("ftokens are still bonded. Will unbond at height 15"));
-    
-    app.next_block(5);
     ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
     agg_resv_price = agg_resv_price_r(&app.deps.storage).load().unwrap();
     assert_eq!(agg_resv_price.uint128_price(), Uint128(75));
+    let unbonding = unbonding_r(&app.deps.storage).load(
+        &to_binary(&app.get_addr("user0").address).unwrap().as_slice()
+    ).unwrap();
+    assert_eq!(unbonding, vec![UnbondingEntry { amount: Uint128(40), mature_height: 25 }]);
 
     // bid below reservation price does not work
     app.change_env("user2", "ft");
@@ -282,6 +307,9 @@ fn test_auction_process() {
             resv_boundary: 500,
             min_bid_inc: 1000u32,
             unlock_threshold: Uint128(5_000),
+            resv_agg: ResvAgg::Mean,
+            resv_vote_lifetime: 0,
+            refund_outbid: false,
         },
     };
     assert_eq!(auc_status, exp_auc_status);
@@ -361,8 +389,9 @@ fn test_auction_process() {
     assert_eq!(Uint128(5_000 - 95), s20_balance(&mut app, "user1"));
 
     // unstake all ftokens (note user0 already unstaked 40, so has 70-40 = 30 ftokens left, 
-    // similar amount to user1)
+    // similar amount to user1). Ftokens are returned immediately, as the vault is closed
     app.change_env("user0", "ft");
+    ft::contract::handle(&mut app.deps, app.env.clone(), ft::msg::HandleMsg::ClaimUnbonded {  }).unwrap();
     msg = ft::msg::HandleMsg::Unstake { amount: Uint128(30) };
     ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
     app.change_env("user1", "ft");
@@ -371,9 +400,12 @@ fn test_auction_process() {
     // user0 and user1 and claim pro-rata sale proceeds
     sim_claim_proceeds(&mut app, "user0").unwrap();
     sim_claim_proceeds(&mut app, "user1").unwrap();
-    // 5000 is sscrt initial balance
+    // 5000 is sscrt initial balance. user1 claims last, so sweeps the rounding dust
     assert_eq!(Uint128(5_000 + 70*95/100), s20_balance(&mut app, "user0"));
-    assert_eq!(Uint128(5_000 - 95 + 30*95/100), s20_balance(&mut app, "user1"));
+    assert_eq!(Uint128(5_000 - 95 + (95 - 70*95/100)), s20_balance(&mut app, "user1"));
+    let sale_proceeds = sale_proceeds_r(&app.deps.storage).load().unwrap();
+    assert_eq!(sale_proceeds.claimed, Uint128(95));
+    assert_eq!(sale_proceeds.claimed_ftokens, Uint128(100));
 }
 
 #[test]
@@ -405,6 +437,1538 @@ fn test_proposals() {
 fn test_proposal_votes() {
 }
 
+#[test]
+fn test_vote_delegation() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 0).unwrap();
+
+    // user1 cannot delegate to itself
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::Delegate { 
+        proposals: Some(app.get_addr("user1").address), resv_price: None 
+    };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("cannot delegate to yourself"));
+
+    // user1 delegates to user0 once unbonded, then stakes
+    app.next_block(10);
+    let msg = ft::msg::HandleMsg::Delegate { 
+        proposals: Some(app.get_addr("user0").address), resv_price: Some(app.get_addr("user0").address) 
+    };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
+    let stake_msg = ft::msg::HandleMsg::Stake { amount: Uint128(30) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), stake_msg).unwrap();
+    let user0_key = to_binary(&app.get_addr("user0").address).unwrap();
+    let deleg_power = deleg_power_r(&app.deps.storage).load(user0_key.as_slice()).unwrap();
+    assert_eq!(deleg_power.proposals, Uint128(30));
+    assert_eq!(deleg_power.resv_price, Uint128(30));
+
+    // delegations cannot be changed while ftokens are bonded
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("ftokens are still bonded"));
+
+    // user0 proposes and votes with own and delegated weight
+    app.change_env("user0", "ft");
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    let msg = ft::msg::HandleMsg::Propose { 
+        title: "config".to_string(), 
+        description: String::new(), 
+        link: None, 
+        proposal: ft::ftoken_mod::msg::Proposal::ChangeConfig { config }, 
+        stake: Uint128(2),
+    };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let msg = ft::msg::HandleMsg::VoteProposal { prop_id: 0u32, vote: Vote::Yes };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.yes, Uint128(90));
+
+    // user1 overrides its delegate on this proposal
+    app.change_env("user1", "ft");
+    let user1_msg = ft::msg::HandleMsg::VoteProposal { prop_id: 0u32, vote: Vote::No };
+    ft::contract::handle(&mut app.deps, app.env.clone(), user1_msg).unwrap();
+    let mut votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!((votes_total.yes, votes_total.no), (Uint128(60), Uint128(30)));
+
+    // changing vote or delegate revoting does not count user1's weight twice 
+    let user1_msg = ft::msg::HandleMsg::VoteProposal { prop_id: 0u32, vote: Vote::Abstain };
+    ft::contract::handle(&mut app.deps, app.env.clone(), user1_msg).unwrap();
+    app.change_env("user0", "ft");
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total, VoteRegister { 
+        yes: Uint128(60), no: Uint128(0), veto: Uint128(0), abstain: Uint128(30) 
+    });
+
+    // user0's reservation price vote includes delegated weight; user1 cannot vote directly
+    let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
+    let agg_resv_price = agg_resv_price_r(&app.deps.storage).load().unwrap();
+    assert_eq!(agg_resv_price.uint128_stake(), Uint128(90));
+    app.change_env("user1", "ft");
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("reservation price vote has been delegated"));
+
+    // unstaking reduces the delegate's reservation price vote immediately, but user1's ftokens 
+    // stay bonded while its delegate's proposal vote is live
+    app.next_block(20);
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(10) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let agg_resv_price = agg_resv_price_r(&app.deps.storage).load().unwrap();
+    assert_eq!(agg_resv_price.uint128_stake(), Uint128(80));
+    let deleg_power = deleg_power_r(&app.deps.storage).load(user0_key.as_slice()).unwrap();
+    assert_eq!((deleg_power.proposals, deleg_power.resv_price), (Uint128(20), Uint128(20)));
+    let unbonding = unbonding_r(&app.deps.storage).load(
+        &to_binary(&app.get_addr("user1").address).unwrap().as_slice()
+    ).unwrap();
+    assert_eq!(unbonding, vec![UnbondingEntry { amount: Uint128(10), mature_height: 210 }]);
+}
+
+
+#[test]
+fn test_secret_ballot() {
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.prop_conf.private_votes = true;
+    fractionalize_with_conf(&mut app, config.clone());
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+    propose(&mut app, "user0", proposal, 2).unwrap();
+    vote_proposal(&mut app, "user0", 0u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user1", 0u32, Vote::No).unwrap();
+
+    // only turnout is visible while voting is live
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::ProposalList { 
+        page: 0, page_size: 10, status: None, proposer: None,
+    }).unwrap();
+    let prop_list = match answer {
+        ft::ftoken_mod::msg::FtokenQueryAnswer::ProposalList(i) => i,
+        _ => panic!("unexpected query answer"),
+    };
+    assert_eq!(prop_list[0].vote_tally, None);
+    assert_eq!(prop_list[0].turnout, Uint128(90));
+
+    // tally is hidden even after end_height until finalized, but a voter can see its own vote
+    app.next_block(200);
+    let msg = ft::msg::QueryMsg::FtokenVkQuery { 
+        address: app.get_addr("user0").address, 
+        key: "vkey".to_string(), 
+        query: ft::ftoken_mod::msg::FtokenAuthQuery::ProposalVotes { prop_id: 0u32 },
+    };
+    app.change_env("user0", "ft");
+    let set_vk = ft::msg::HandleMsg::SetViewingKey { key: "vkey".to_string(), padding: None };
+    ft::contract::handle(&mut app.deps, app.env.clone(), set_vk).unwrap();
+    let answer: ft::msg::QueryAnswer = from_binary(&ft::contract::query(&app.deps, msg.clone()).unwrap()).unwrap();
+    let vote = match answer {
+        ft::msg::QueryAnswer::FtokenQueryAnswer(ft::ftoken_mod::msg::FtokenQueryAnswer::ProposalVotes(i)) => i,
+        _ => panic!("unexpected query answer"),
+    };
+    assert_eq!(vote, VoteRegister { yes: Uint128(60), no: Uint128(0), veto: Uint128(0), abstain: Uint128(0) });
+
+    // full tally revealed once finalized
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 0u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize.clone()).unwrap();
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::ProposalList { 
+        page: 0, page_size: 10, status: None, proposer: None,
+    }).unwrap();
+    let prop_list = match answer {
+        ft::ftoken_mod::msg::FtokenQueryAnswer::ProposalList(i) => i,
+        _ => panic!("unexpected query answer"),
+    };
+    assert_eq!(prop_list[0].prop_info.outcome, Some(VoteResult::Won));
+    assert_eq!(prop_list[0].vote_tally, Some(VoteRegister {
+        yes: Uint128(60), no: Uint128(30), veto: Uint128(0), abstain: Uint128(0)
+    }));
+    assert!(ft::contract::query(&app.deps, msg).is_ok());
+
+    // cannot finalize twice
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), finalize));
+    assert!(error.contains("proposal has already been finalized"));
+
+    // tally stays hidden on a proposal cancelled when the vault closes
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::ChangeConfig { config }, 2).unwrap();
+    vote_proposal(&mut app, "user1", 1u32, Vote::No).unwrap();
+    for user in ["user0", "user1"] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+    sim_bid(&mut app, 100, Some("user2")).unwrap();
+    app.next_block(100);
+    sim_finalize_auction(&mut app).unwrap();
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::ProposalList { 
+        page: 0, page_size: 10, status: Some(PropStatus::Cancelled), proposer: None,
+    }).unwrap();
+    let prop_list = match answer {
+        ft::ftoken_mod::msg::FtokenQueryAnswer::ProposalList(i) => i,
+        _ => panic!("unexpected query answer"),
+    };
+    assert_eq!(prop_list[0].prop_info.prop_id, 1u32);
+    assert_eq!(prop_list[0].vote_tally, None);
+    assert_eq!(prop_list[0].turnout, Uint128(30));
+}
+
+#[test]
+fn test_voting_power_snapshots() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+
+    // proposal 0 snapshots voting power at height 5
+    app.next_block(5);
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+    propose(&mut app, "user0", proposal.clone(), 2).unwrap();
+
+    // staking after the snapshot does not add weight to proposal 0, but does to proposal 1
+    app.next_block(1);
+    let msg = ft::msg::HandleMsg::Stake { amount: Uint128(6) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    propose(&mut app, "user0", proposal, 2).unwrap();
+    vote_proposal(&mut app, "user0", 0u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user0", 1u32, Vote::Yes).unwrap();
+    let votes_total_0 = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    let votes_total_1 = votes_total_r(&app.deps.storage).load(&1u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total_0.yes, Uint128(60));
+    assert_eq!(votes_total_1.yes, Uint128(66));
+
+    // historical voting power
+    let user0 = app.get_addr("user0").address;
+    assert_eq!(get_power_at(&app.deps.storage, &user0, 5u64).unwrap().staked, Uint128(60));
+    assert_eq!(get_power_at(&app.deps.storage, &user0, 6u64).unwrap().staked, Uint128(66));
+    assert_eq!(get_power_at(&app.deps.storage, &app.get_addr("user2").address, 6u64).unwrap(), PowerCheckpoint::default());
+}
+
+#[test]
+fn test_vote_reweighting() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 20).unwrap();
+
+    // staking more increases existing reservation price vote
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let msg = ft::msg::HandleMsg::Stake { amount: Uint128(4) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let agg_resv_price = agg_resv_price_r(&app.deps.storage).load().unwrap();
+    assert_eq!((agg_resv_price.uint128_stake(), agg_resv_price.uint128_price()), (Uint128(64), Uint128(100)));
+
+    // proposal 0 snapshots user1's voting power of 20
+    app.next_block(10);
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::ChangeConfig { config }, 2).unwrap();
+
+    // user1 unstakes before voting, so vote counts current (lower) voting power
+    app.next_block(1);
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(10) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    vote_proposal(&mut app, "user1", 0u32, Vote::Yes).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.yes, Uint128(10));
+
+    // restaking re-weights the live vote, up to voting power at snapshot height
+    let msg = ft::msg::HandleMsg::Stake { amount: Uint128(10) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.yes, Uint128(20));
+    app.next_block(10);
+    ft::contract::handle(&mut app.deps, app.env.clone(), ft::msg::HandleMsg::ClaimUnbonded {  }).unwrap();
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total, VoteRegister { 
+        yes: Uint128(20), no: Uint128(0), veto: Uint128(0), abstain: Uint128(0) 
+    });
+}
+
+#[test]
+fn test_quadratic_voting() {
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.prop_conf.vote_scheme = VoteScheme::Quadratic;
+    fractionalize_with_conf(&mut app, config.clone());
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 16, 60, 16).unwrap();
+    transfer_ftkn_and_stake(&mut app, "user0", "user2", 16, 0, 16).unwrap();
+
+    // proposal 0 switches back to linear voting, and passes with user0's vote alone
+    let mut linear_config = config.clone();
+    linear_config.prop_conf.vote_scheme = VoteScheme::Linear;
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config: linear_config };
+    propose(&mut app, "user0", proposal, 2).unwrap();
+    vote_proposal(&mut app, "user0", 0u32, Vote::Yes).unwrap();
+
+    // vote weight is the square root of voting power, rounded down
+    app.next_block(100);
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+    propose(&mut app, "user0", proposal, 2).unwrap();
+    vote_proposal(&mut app, "user0", 1u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user1", 1u32, Vote::No).unwrap();
+    vote_proposal(&mut app, "user2", 1u32, Vote::No).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&1u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total, VoteRegister { 
+        yes: Uint128(7), no: Uint128(8), veto: Uint128(0), abstain: Uint128(0) 
+    });
+
+    // scheme is snapshotted in the proposal
+    app.next_block(100);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 0u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    assert_eq!(config.prop_conf.vote_scheme, VoteScheme::Linear);
+    vote_proposal(&mut app, "user2", 1u32, Vote::Yes).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&1u32.to_le_bytes()).unwrap();
+    assert_eq!((votes_total.yes, votes_total.no), (Uint128(11), Uint128(4)));
+    vote_proposal(&mut app, "user2", 1u32, Vote::No).unwrap();
+
+    // two small holders outvote a large one
+    app.next_block(100);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 1u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    let prop_info = props_r(&app.deps.storage).load(&1u32.to_le_bytes()).unwrap();
+    assert_eq!(prop_info.vote_scheme, VoteScheme::Quadratic);
+    assert_eq!(prop_info.outcome, Some(VoteResult::Lost));
+}
+
+/// quorum, veto and emergency thresholds are measured with voting power as a proportion of the total 
+/// supply, so vote schemes cannot inflate them
+#[test]
+fn test_vote_scheme_thresholds() {
+    // quadratic: 2 holders of 4 ftokens each have a vote weight of 4, which is 40% of the square root 
+    // of the total supply, but only 8% of the total supply
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.prop_conf.vote_scheme = VoteScheme::Quadratic;
+    fractionalize_with_conf(&mut app, config.clone());
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 4, 80, 4).unwrap();
+    transfer_ftkn_and_stake(&mut app, "user0", "user2", 4, 0, 4).unwrap();
+
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config: config.clone() };
+    propose(&mut app, "user0", proposal.clone(), 2).unwrap();
+    propose(&mut app, "user0", proposal, 2).unwrap();
+    for user in ["user1", "user2"] {
+        vote_proposal(&mut app, user, 0u32, Vote::Veto).unwrap();
+        vote_proposal(&mut app, user, 1u32, Vote::Yes).unwrap();
+    }
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.veto, Uint128(4));
+    let powers_total = vote_powers_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(powers_total.veto, Uint128(8));
+
+    // 8% is below the 10% veto threshold and the 20% quorum
+    app.next_block(200);
+    for prop_id in [0u32, 1u32] {
+        let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id };
+        ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+        let prop_info = props_r(&app.deps.storage).load(&prop_id.to_le_bytes()).unwrap();
+        assert_eq!(prop_info.outcome, Some(VoteResult::Lost));
+    }
+
+    // conviction: votes cast at the start of the voting period carry 3x weight, but count 1x 
+    // towards the quorum and emergency threshold
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.prop_conf.vote_scheme = VoteScheme::Conviction { max_multiplier: 3 };
+    fractionalize_with_conf(&mut app, config.clone());
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 10, 30).unwrap();
+
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::RotateViewingKey {  }, 2).unwrap();
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+    propose(&mut app, "user0", proposal, 2).unwrap();
+    vote_proposal(&mut app, "user1", 0u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user0", 1u32, Vote::Yes).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.yes, Uint128(90));
+
+    // 30% of the total supply is below the 66.67% emergency threshold
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 0u32 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), finalize));
+    assert!(error.contains("proposal still in voting"));
+
+    // 10% of the total supply is below the 20% quorum, despite a vote weight of 30
+    app.next_block(200);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 1u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&1u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.yes, Uint128(30));
+    let prop_info = props_r(&app.deps.storage).load(&1u32.to_le_bytes()).unwrap();
+    assert_eq!(prop_info.outcome, Some(VoteResult::Lost));
+}
+
+#[test]
+fn test_conviction_voting() {
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.prop_conf.vote_scheme = VoteScheme::Conviction { max_multiplier: 3 };
+    fractionalize_with_conf(&mut app, config.clone());
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 20).unwrap();
+
+    // vote cast when the proposal is made gets the max multiplier
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+    propose(&mut app, "user0", proposal, 2).unwrap();
+    vote_proposal(&mut app, "user0", 0u32, Vote::Yes).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.yes, Uint128(180));
+
+    // user1 unstakes before voting halfway through the voting period: 1x + 2x * 100/200
+    app.next_block(100);
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(10) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    vote_proposal(&mut app, "user1", 0u32, Vote::No).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.no, Uint128(20));
+
+    // restaking commits the additional voting power from the current height only: 
+    // commitment height averages to 150 -> 20 * (1 + 2 * 50/200) 
+    app.next_block(100);
+    let msg = ft::msg::HandleMsg::Stake { amount: Uint128(10) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.no, Uint128(30));
+
+    // changing vote resets commitment, so the vote counts 1x at the end of the voting period
+    vote_proposal(&mut app, "user0", 0u32, Vote::Abstain).unwrap();
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total, VoteRegister { 
+        yes: Uint128(0), no: Uint128(30), veto: Uint128(0), abstain: Uint128(60) 
+    });
+}
+
+#[test]
+fn test_vault_treasury() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+    let s20_addr = app.get_addr("s20").address;
+
+    // bid token is registered with the treasury at init, and can be sent to the ftoken contract
+    sim_send_s20_to_ft(&mut app, "user2", 1_000).unwrap();
+    app.change_env("user2", "ft");
+    let msg = ft::msg::HandleMsg::Receive(ft::receiver::Snip20ReceiveMsg::new(
+        app.get_addr("user2").address, app.get_addr("user2").address, Uint128(1_000), None, None,
+    ));
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("token is not registered with the vault treasury"));
+
+    // ftokens can be deposited too
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::TreasuryDeposit { amount: Uint128(4) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::TreasuryBalance {  }).unwrap();
+    let treasury = match answer {
+        ft::ftoken_mod::msg::FtokenQueryAnswer::TreasuryBalance(i) => i,
+        _ => panic!("unexpected query answer"),
+    };
+    assert_eq!((treasury.snip20s[0].balance, treasury.ftoken), (Uint128(1_000), Uint128(4)));
+
+    // spend proposals cannot exceed treasury balance
+    let user1_addr = app.get_addr("user1").address;
+    let payout = |amount: u128| ft::ftoken_mod::msg::Proposal::TreasurySpend { 
+        token: Some(s20_addr.clone()), 
+        payouts: vec![ft::ftoken_mod::msg::Payout { recipient: user1_addr.clone(), amount: Uint128(amount) }],
+    };
+    let error = extract_error_msg(propose(&mut app, "user0", payout(1_001), 2));
+    assert!(error.contains("insufficient treasury balance: 1000 available"));
+    propose(&mut app, "user0", payout(400), 2).unwrap();
+    vote_proposal(&mut app, "user0", 0u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user1", 0u32, Vote::Yes).unwrap();
+
+    // passed proposal pays out from treasury
+    app.next_block(200);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 0u32 };
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    assert_eq!(handle_resp.messages.len(), 1);
+    app.change_env("ft", "s20");
+    let msg = extract_cmsg_check_env::<s20::msg::HandleMsg>(&app, &handle_resp.messages[0]).unwrap();
+    s20::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    assert_eq!(Uint128(5_000 + 400), s20_balance(&mut app, "user1"));
+
+    // buyout
+    for user in ["user0", "user1"] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+    sim_bid(&mut app, 100, Some("user2")).unwrap();
+    app.next_block(100);
+    sim_finalize_auction(&mut app).unwrap();
+
+    // leftover treasury funds are distributed pro rata with proceeds, over ftokens not held by the treasury
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(60) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(30) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    sim_claim_proceeds(&mut app, "user0").unwrap();
+    sim_claim_proceeds(&mut app, "user1").unwrap();
+    // user0 holds 66 ftokens (after treasury deposit, including its retrievable proposal stake) out of 
+    // 96 not held by the treasury. user1 burns the last 30, so receives what remains of both pools
+    assert_eq!(Uint128(5_000 + 100*66/96 + 600*66/96), s20_balance(&mut app, "user0"));
+    assert_eq!(Uint128(5_000 + 400 + (100 - 100*66/96) + (600 - 600*66/96)), s20_balance(&mut app, "user1"));
+    let treasury = treasury_r(&app.deps.storage).load().unwrap();
+    assert_eq!(treasury.snip20s[0].balance, Uint128(0));
+}
+
+#[test]
+fn test_slashed_prop_stake() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+
+    // one vetoed proposal for each slashing destination (proposals 0, 2 and 4). The slashing destination 
+    // is snapshotted when a proposal is made, so the DAO changes it in between (proposals 1 and 3)
+    let mut config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config: config.clone() };
+    for (slash_dest, stake) in [(SlashDest::Burn, 2), (SlashDest::Treasury, 2), (SlashDest::VetoVoters, 5)] {
+        if config.prop_conf.slash_dest != slash_dest {
+            config.prop_conf.slash_dest = slash_dest;
+            change_config(&mut app, "user0", config.clone()).unwrap();
+        }
+        let prop_id = prop_id_r(&app.deps.storage).load().unwrap();
+        propose(&mut app, "user0", proposal.clone(), stake).unwrap();
+        vote_proposal(&mut app, "user0", prop_id, Vote::Veto).unwrap();
+        vote_proposal(&mut app, "user1", prop_id, Vote::Veto).unwrap();
+    }
+    app.next_block(200);
+    for prop_id in [0u32, 2u32, 4u32] {
+        let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id };
+        ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    }
+    
+    // burn reduces total supply
+    let total_supply = ft::state::ReadonlyConfig::from_storage(&app.deps.storage).total_supply();
+    assert_eq!(total_supply, 98);
+    assert_eq!(ftoken_balance(&mut app, "ft"), Uint128(90 + 2 + 5));
+
+    // treasury receives stake
+    let treasury = treasury_r(&app.deps.storage).load().unwrap();
+    assert_eq!(treasury.ftoken, Uint128(2));
+
+    // veto voters claim pro rata to their voting power. 5 * 30/90 rounds down to 1
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::ClaimSlashedStake { prop_id: 2u32 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("proposal stake is not distributed to veto voters"));
+    let msg = ft::msg::HandleMsg::ClaimSlashedStake { prop_id: 4u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user1"), Uint128(1));
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()));
+    assert!(error.contains("you have already claimed your share of the slashed stake"));
+    // user0 claims last, so receives the rounding remainder, and no slashed stake is left in the contract
+    app.change_env("user0", "ft");
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(10 - 9 + 4));
+    assert_eq!(ftoken_balance(&mut app, "ft"), Uint128(90 + 2));
+    app.change_env("user2", "ft");
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("you did not vote veto on this proposal"));
+}
+
+#[test]
+fn test_cancel_proposal() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+
+    // only the proposer can cancel, which refunds the stake
+    propose(&mut app, "user0", proposal.clone(), 2).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(8));
+    let msg = ft::msg::HandleMsg::CancelProposal { prop_id: 0u32 };
+    app.change_env("user1", "ft");
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()));
+    assert!(error.contains("only the proposer can cancel a proposal"));
+    app.change_env("user0", "ft");
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(10));
+    let prop_info = props_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(prop_info.outcome, Some(VoteResult::Cancelled));
+    let error = extract_error_msg(vote_proposal(&mut app, "user1", 0u32, Vote::Yes));
+    assert!(error.contains("proposal has been cancelled"));
+    let msg = ft::msg::HandleMsg::RetrievePropStake { prop_id: 0u32 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("proposal was cancelled"));
+
+    // after the cancellation period, proposals with votes cannot be cancelled
+    propose(&mut app, "user0", proposal.clone(), 2).unwrap();
+    propose(&mut app, "user0", proposal, 2).unwrap();
+    vote_proposal(&mut app, "user1", 1u32, Vote::Yes).unwrap();
+    app.next_block(20);
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::CancelProposal { prop_id: 1u32 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("proposal has votes, and the cancellation period ended"));
+
+    // pending proposals are treated as cancelled once the vault closes. Finalizing or retrieving 
+    // the stake refunds the proposer
+    for user in ["user0", "user1"] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+    sim_bid(&mut app, 100, Some("user2")).unwrap();
+    app.next_block(100);
+    sim_finalize_auction(&mut app).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(6));
+    let error = extract_error_msg(vote_proposal(&mut app, "user1", 2u32, Vote::Yes));
+    assert!(error.contains("proposal has been cancelled"));
+    app.change_env("user0", "ft");
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 1u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize.clone()).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(8));
+    let retrieve = ft::msg::HandleMsg::RetrievePropStake { prop_id: 2u32 };
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), retrieve.clone()).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(10));
+    let answer: ft::msg::HandleAnswer = from_binary(&handle_resp.data.unwrap()).unwrap();
+    assert!(matches!(answer, ft::msg::HandleAnswer::RetrievePropStake { .. }));
+    assert_eq!(handle_resp.log, vec![log("cancelled_prop_id", 2u32), log("refunded_stake", Uint128(2))]);
+    for prop_id in 1..3u32 {
+        let prop_info = props_r(&app.deps.storage).load(&prop_id.to_le_bytes()).unwrap();
+        assert_eq!((prop_info.outcome, prop_info.stake_withdrawn), (Some(VoteResult::Cancelled), true));
+    }
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), finalize));
+    assert!(error.contains("proposal has already been finalized"));
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), retrieve));
+    assert!(error.contains("proposal was cancelled"));
+}
+
+#[test]
+fn test_close_vault_with_many_proposals() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 20).unwrap();
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+
+    // 40 finalized proposals, and 5 still pending when the vault closes
+    for prop_id in 0..40u32 {
+        propose(&mut app, "user0", proposal.clone(), 2).unwrap();
+        app.next_block(200);
+        app.change_env("user0", "ft");
+        let msg = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+        let msg = ft::msg::HandleMsg::RetrievePropStake { prop_id };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+    for _ in 0..5u32 {
+        propose(&mut app, "user0", proposal.clone(), 2).unwrap();
+    }
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(0));
+
+    // closing the vault leaves the proposals untouched
+    for user in ["user0", "user1"] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+    sim_bid(&mut app, 100, Some("user2")).unwrap();
+    app.next_block(100);
+    sim_finalize_auction(&mut app).unwrap();
+    for prop_id in 40..45u32 {
+        let prop_info = props_r(&app.deps.storage).load(&prop_id.to_le_bytes()).unwrap();
+        assert_eq!((prop_info.outcome, prop_info.stake_withdrawn), (None, false));
+    }
+
+    // pending proposals are listed as cancelled
+    let query = ft::ftoken_mod::msg::FtokenQuery::ProposalList { 
+        page: 0, page_size: 10, status: Some(PropStatus::Cancelled), proposer: None,
+    };
+    let prop_ids = match ftoken_query(&app, query).unwrap() {
+        ft::ftoken_mod::msg::FtokenQueryAnswer::ProposalList(i) => i.iter().map(|p| p.prop_info.prop_id).collect::<Vec<u32>>(),
+        _ => panic!("unexpected query answer"),
+    };
+    assert_eq!(prop_ids, vec![44, 43, 42, 41, 40]);
+
+    // the stakes of the cancelled proposals count towards the proposer's claim on the sale proceeds:
+    // 60 staked + 5 * 2 proposal stakes, so 70 out of 100 ftokens, rounded down
+    let s20_before = s20_balance(&mut app, "user0");
+    sim_claim_proceeds(&mut app, "user0").unwrap();
+    assert_eq!(s20_balance(&mut app, "user0"), s20_before + Uint128(69));
+    for prop_id in 40..45u32 {
+        let prop_info = props_r(&app.deps.storage).load(&prop_id.to_le_bytes()).unwrap();
+        assert_eq!((prop_info.outcome, prop_info.stake_withdrawn), (Some(VoteResult::Cancelled), true));
+    }
+}
+
+#[test]
+fn test_proposal_list() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 20).unwrap();
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+
+    // metadata length is bounded
+    let propose_msg = |title: String| ft::msg::HandleMsg::Propose { 
+        title, 
+        description: "description".to_string(), 
+        link: Some("https://example.com".to_string()), 
+        proposal: proposal.clone(), 
+        stake: Uint128(2),
+    };
+    app.change_env("user0", "ft");
+    let msg = propose_msg("t".repeat(65));
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("proposal title cannot exceed 64 characters"));
+    ft::contract::handle(&mut app.deps, app.env.clone(), propose_msg("title".to_string())).unwrap();
+
+    // props 0, 2, 4 by user0; props 1, 3 by user1. Prop 3 is cancelled
+    for prop_id in 1..5u32 {
+        let proposer = ["user0", "user1"][prop_id as usize % 2];
+        propose(&mut app, proposer, proposal.clone(), 2).unwrap();
+    }
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::CancelProposal { prop_id: 3u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+
+    let list = |app: &App, page: u32, page_size: u32, status: Option<PropStatus>, proposer: Option<&str>| {
+        let query = ft::ftoken_mod::msg::FtokenQuery::ProposalList { 
+            page, page_size, status, proposer: proposer.map(|i| app.get_addr(i).address),
+        };
+        match ftoken_query(app, query).unwrap() {
+            ft::ftoken_mod::msg::FtokenQueryAnswer::ProposalList(i) => i.iter().map(|p| p.prop_info.prop_id).collect::<Vec<u32>>(),
+            _ => panic!("unexpected query answer"),
+        }
+    };
+
+    // latest first, paginated
+    assert_eq!(list(&app, 0, 2, None, None), vec![4, 3]);
+    assert_eq!(list(&app, 2, 2, None, None), vec![0]);
+    assert_eq!(list(&app, 3, 2, None, None), Vec::<u32>::new());
+    
+    // filters
+    assert_eq!(list(&app, 0, 10, None, Some("user0")), vec![4, 2, 0]);
+    assert_eq!(list(&app, 1, 2, None, Some("user0")), vec![0]);
+    assert_eq!(list(&app, 0, 10, Some(PropStatus::Active), None), vec![4, 2, 1, 0]);
+    assert_eq!(list(&app, 0, 10, Some(PropStatus::Active), Some("user1")), vec![1]);
+    assert_eq!(list(&app, 0, 10, Some(PropStatus::Cancelled), None), vec![3]);
+    assert_eq!(list(&app, 0, 10, Some(PropStatus::Passed), None), Vec::<u32>::new());
+
+    // passed proposals that were executed on finalization
+    vote_proposal(&mut app, "user0", 0u32, Vote::Yes).unwrap();
+    app.next_block(200);
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 0u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    assert_eq!(list(&app, 0, 10, Some(PropStatus::Passed), None), vec![0]);
+    assert_eq!(list(&app, 0, 10, Some(PropStatus::Executed), None), vec![0]);
+    assert_eq!(list(&app, 0, 10, Some(PropStatus::Executed), Some("user1")), Vec::<u32>::new());
+
+    // metadata is stored with the proposal
+    let prop_info = props_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(
+        (prop_info.title, prop_info.description, prop_info.link), 
+        ("title".to_string(), "description".to_string(), Some("https://example.com".to_string()))
+    );
+}
+
+#[test]
+fn test_prop_type_thresholds() {
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.prop_conf.threshold_overrides = vec![ThresholdOverride {
+        prop_type: PropType::ChangeConfig, vote_quorum: None, pass_threshold: Some(Uint128(6667)), veto_threshold: None,
+    }];
+    fractionalize_with_conf(&mut app, config.clone());
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+
+    // resulting thresholds are queryable for each proposal type
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::ProposalConfig {  }).unwrap();
+    let vote_thresholds = match answer {
+        ft::ftoken_mod::msg::FtokenQueryAnswer::ProposalConfig { vote_thresholds, .. } => vote_thresholds,
+        _ => panic!("unexpected query answer"),
+    };
+    assert_eq!(vote_thresholds.len(), 8);
+    assert_eq!(vote_thresholds[1], VoteThresholds { 
+        prop_type: PropType::ChangeConfig, vote_quorum: Uint128(2000), pass_threshold: Uint128(6667), veto_threshold: Uint128(1000),
+    });
+    assert_eq!(vote_thresholds[2].pass_threshold, Uint128(5000));
+
+    // 2/3 of votes pass a simple majority, but not the supermajority of config changes
+    let token = app.get_addr("s721");
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::ChangeConfig { config }, 2).unwrap();
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::RegisterTreasuryToken { token }, 2).unwrap();
+    for prop_id in 0..2u32 {
+        vote_proposal(&mut app, "user0", prop_id, Vote::Yes).unwrap();
+        vote_proposal(&mut app, "user1", prop_id, Vote::No).unwrap();
+    }
+    app.next_block(200);
+    for prop_id in 0..2u32 {
+        let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id };
+        ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    }
+    let outcomes: Vec<Option<VoteResult>> = (0..2u32)
+        .map(|prop_id| props_r(&app.deps.storage).load(&prop_id.to_le_bytes()).unwrap().outcome)
+        .collect();
+    assert_eq!(outcomes, vec![Some(VoteResult::Lost), Some(VoteResult::Won)]);
+}
+
+#[test]
+fn test_rotate_nft_viewing_key() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+    let old_vk = nft_vk_r(&app.deps.storage).load().unwrap().to_string();
+
+    let error = extract_error_msg(propose(
+        &mut app, "user0", ft::ftoken_mod::msg::Proposal::RevokePermits { permit_names: vec![] }, 2
+    ));
+    assert!(error.contains("no permits to revoke"));
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::RotateViewingKey {  }, 2).unwrap();
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::ChangeConfig { config }, 2).unwrap();
+
+    // below the emergency threshold, the proposal cannot be executed before voting ends
+    vote_proposal(&mut app, "user0", 0u32, Vote::Yes).unwrap();
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 0u32 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), finalize.clone()));
+    assert!(error.contains("proposal still in voting"));
+
+    // only access revocation proposals can be executed early
+    vote_proposal(&mut app, "user0", 1u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user1", 1u32, Vote::Yes).unwrap();
+    let msg = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 1u32 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("proposal still in voting"));
+
+    // supermajority executes the rotation immediately
+    vote_proposal(&mut app, "user1", 0u32, Vote::Yes).unwrap();
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    let prop_info = props_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(prop_info.outcome, Some(VoteResult::Won));
+    let new_vk = nft_vk_r(&app.deps.storage).load().unwrap().to_string();
+    assert_ne!(new_vk, old_vk);
+    app.change_env("ft", "s721");
+    let msg = extract_cmsg_check_env::<s721::msg::HandleMsg>(&app, &handle_resp.messages[0]).unwrap();
+    match &msg {
+        s721::msg::HandleMsg::SetViewingKey { key, .. } => assert_eq!(key, &new_vk),
+        _ => panic!("unexpected message"),
+    }
+    s721::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+}
+
+#[test]
+fn test_nft_msg_policies() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+    let revoke_all = ft::ftoken_mod::msg::Proposal::MsgToNft { 
+        msg: ft::ftoken_mod::msg::AllowedNftMsg::RevokeAll { operator: app.get_addr("user2").address },
+    };
+    let reveal = ft::ftoken_mod::msg::Proposal::MsgToNft { 
+        msg: ft::ftoken_mod::msg::AllowedNftMsg::Reveal { },
+    };
+
+    // if not given, the policies enable the messages that were always allowed
+    let mut config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    let baseline = vec![
+        NftMsgType::SetMetadata, NftMsgType::Reveal, NftMsgType::MakeOwnershipPrivate,
+        NftMsgType::SetGlobalApproval, NftMsgType::SetWhitelistedApproval,
+    ];
+    let msg_types = |policies: &Vec<NftMsgPolicy>| policies.iter().map(|i| i.msg_type.clone()).collect::<Vec<NftMsgType>>();
+    // `nft_msg_policies` is the last field
+    let json = String::from_utf8(to_binary(&config.prop_conf).unwrap().0).unwrap();
+    let json = format!("{}}}", json.split(",\"nft_msg_policies\"").next().unwrap());
+    let prop_conf: PropConf = from_slice(json.as_bytes()).unwrap();
+    assert_eq!(msg_types(&prop_conf.nft_msg_policies), baseline);
+    assert_eq!(msg_types(&PropConf::default().nft_msg_policies), baseline);
+
+    // other messages are disabled unless the DAO enables them
+    let error = extract_error_msg(propose(&mut app, "user0", revoke_all.clone(), 2));
+    assert!(error.contains("this message to the underlying NFT is disabled"));
+    config.prop_conf.nft_msg_policies.push(NftMsgPolicy { 
+        msg_type: NftMsgType::RevokeAll, vote_quorum: None, pass_threshold: Some(Uint128(8000)), veto_threshold: None,
+    });
+    change_config(&mut app, "user0", config).unwrap();
+
+    // per-message thresholds apply
+    propose(&mut app, "user0", revoke_all, 2).unwrap();
+    propose(&mut app, "user0", reveal, 2).unwrap();
+    for prop_id in 1..3u32 {
+        vote_proposal(&mut app, "user0", prop_id, Vote::Yes).unwrap();
+        vote_proposal(&mut app, "user1", prop_id, Vote::No).unwrap();
+    }
+    app.next_block(200);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 1u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    let prop_info = props_r(&app.deps.storage).load(&1u32.to_le_bytes()).unwrap();
+    assert_eq!(prop_info.outcome, Some(VoteResult::Lost));
+
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 2u32 };
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    let prop_info = props_r(&app.deps.storage).load(&2u32.to_le_bytes()).unwrap();
+    assert_eq!(prop_info.outcome, Some(VoteResult::Won));
+    app.change_env("ft", "s721");
+    let msg = extract_cmsg_check_env::<s721::msg::HandleMsg>(&app, &handle_resp.messages[0]).unwrap();
+    assert!(matches!(msg, s721::msg::HandleMsg::Reveal { .. }));
+}
+
+#[test]
+fn test_vault_status() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+    let set_status = |status: VaultStatus| ft::msg::HandleMsg::SetVaultStatus { status };
+
+    // only guardians can set the vault status directly
+    app.change_env("user2", "ft");
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), set_status(VaultStatus::StopAll)));
+    assert!(error.contains("only guardians can change the vault status"));
+    let mut config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    config.guardians = vec![app.get_addr("user2").address];
+    change_config(&mut app, "user0", config.clone()).unwrap();
+
+    // pausing governance blocks proposals, but not reservation price votes
+    app.change_env("user2", "ft");
+    ft::contract::handle(&mut app.deps, app.env.clone(), set_status(VaultStatus::StopGovernance)).unwrap();
+    let error = extract_error_msg(propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::ChangeConfig { config }, 2));
+    assert!(error.contains("operation paused. Vault status is StopGovernance"));
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+
+    // pausing auctions blocks bids and reservation price votes
+    app.change_env("user2", "ft");
+    ft::contract::handle(&mut app.deps, app.env.clone(), set_status(VaultStatus::StopAuction)).unwrap();
+    let error = extract_error_msg(sim_bid(&mut app, 1000, Some("user2")));
+    assert!(error.contains("operation paused. Vault status is StopAuction"));
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("operation paused. Vault status is StopAuction"));
+
+    // stopping all blocks staking, but the DAO can still resume the vault with a supermajority
+    app.change_env("user2", "ft");
+    ft::contract::handle(&mut app.deps, app.env.clone(), set_status(VaultStatus::StopAll)).unwrap();
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::Stake { amount: Uint128(1) };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg));
+    assert!(error.contains("operation paused. Vault status is StopAll"));
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::SetVaultStatus { status: VaultStatus::Normal }, 2).unwrap();
+    vote_proposal(&mut app, "user0", 1u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user1", 1u32, Vote::Yes).unwrap();
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 1u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::VaultStatus {  }).unwrap();
+    assert_eq!(answer, ft::ftoken_mod::msg::FtokenQueryAnswer::VaultStatus(VaultStatus::Normal));
+}
+
+#[test]
+fn test_tender_offer() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 0, 0).unwrap();
+
+    // user2 escrows 400 sSCRT to buy up to 40 ftokens at 10 each
+    app.change_env("user2", "s20");
+    let msg = s20::msg::HandleMsg::IncreaseAllowance { 
+        spender: app.get_addr("ft").address, amount: Uint128(400), expiration: None, padding: None 
+    };
+    s20::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    app.change_env("user2", "ft");
+    let msg = ft::msg::HandleMsg::MakeTenderOffer { amount: Uint128(40), price: Uint128(10), period: 50 };
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000 - 400));
+
+    // holders tender up to the offer amount, and are paid immediately
+    let tender = |amount: u128| ft::msg::HandleMsg::Tender { offer_id: 0u32, amount: Uint128(amount) };
+    app.change_env("user0", "ft");
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), tender(41)));
+    assert!(error.contains("tender offer can only accept 40 more ftokens"));
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), tender(25)).unwrap();
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000 + 250));
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(45));
+    app.change_env("user1", "ft");
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), tender(10)).unwrap();
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+
+    // offer cannot be settled while open
+    let settle = ft::msg::HandleMsg::SettleTenderOffer { offer_id: 0u32 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), settle.clone()));
+    assert!(error.contains("tender offer is open until height 50"));
+
+    // after expiry, tendered ftokens go to the offeror, and unspent escrow is refunded
+    app.next_block(51);
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), tender(1)));
+    assert!(error.contains("tender offer has expired"));
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), settle.clone()).unwrap();
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user2"), Uint128(35));
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000 - 400 + 50));
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), settle));
+    assert!(error.contains("tender offer has already been settled"));
+
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::TenderOfferList { page: 0, page_size: 10 }).unwrap();
+    let offers = match answer {
+        ft::ftoken_mod::msg::FtokenQueryAnswer::TenderOfferList(i) => i,
+        _ => panic!("unexpected query answer"),
+    };
+    assert_eq!(offers.len(), 1);
+    assert_eq!((offers[0].tendered, offers[0].settled), (Uint128(35), true));
+}
+
+#[test]
+fn test_buyout_offer() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+    app.change_env("user2", "s20");
+    let msg = s20::msg::HandleMsg::IncreaseAllowance { 
+        spender: app.get_addr("ft").address, amount: Uint128(4_000), expiration: None, padding: None 
+    };
+    s20::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let make_offer = |app: &mut App, amount: u128, period: u64| {
+        app.change_env("user2", "ft");
+        let msg = ft::msg::HandleMsg::MakeBuyoutOffer { amount: Uint128(amount), period };
+        let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+        sim_ft_msgs_to_s20(app, &handle_resp).unwrap();
+    };
+    let accept = |offer_id: u32| ft::ftoken_mod::msg::Proposal::AcceptOffer { offer_id };
+    let withdraw = |offer_id: u32| ft::msg::HandleMsg::WithdrawBuyoutOffer { offer_id };
+
+    // offers need to stay binding through the voting period
+    make_offer(&mut app, 1_000, 100);
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000 - 1_000));
+    let error = extract_error_msg(propose(&mut app, "user0", accept(0), 2));
+    assert!(error.contains("buyout offer expires at height 100, before the proposal voting period ends"));
+    make_offer(&mut app, 800, 300);
+    propose(&mut app, "user0", accept(1), 2).unwrap();
+    let error = extract_error_msg(propose(&mut app, "user0", accept(1), 2));
+    assert!(error.contains("proposal 0 to accept this buyout offer is pending"));
+    app.change_env("user2", "ft");
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), withdraw(1)));
+    assert!(error.contains("proposal to accept this buyout offer has not been finalized"));
+
+    // rejected and expired offers can be withdrawn
+    vote_proposal(&mut app, "user0", 0u32, Vote::No).unwrap();
+    app.next_block(200);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 0u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    for offer_id in 0..2u32 {
+        app.change_env("user2", "ft");
+        let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), withdraw(offer_id)).unwrap();
+        sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    }
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000));
+
+    // a passed proposal does not accept an offer that expired before the proposal was finalized
+    make_offer(&mut app, 500, 200);
+    propose(&mut app, "user0", accept(2), 2).unwrap();
+    vote_proposal(&mut app, "user0", 1u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user1", 1u32, Vote::Yes).unwrap();
+    app.next_block(201);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 1u32 };
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    assert!(handle_resp.messages.is_empty());
+    assert_eq!(handle_resp.log, vec![log("not_executed", 1u32)]);
+    let prop_info = props_r(&app.deps.storage).load(&1u32.to_le_bytes()).unwrap();
+    assert_eq!(prop_info.outcome, Some(VoteResult::Won));
+    assert!(!prop_info.executed);
+    assert!(ftoken_info_r(&app.deps.storage).load().unwrap().vault_active);
+    let offer = buyout_offers_r(&app.deps.storage).load(&2u32.to_le_bytes()).unwrap();
+    assert!(!offer.accepted);
+    let token: s721::token::Token = json_load(
+        &ReadonlyPrefixedStorage::new(PREFIX_INFOS, &app.deps.storage), &0u32.to_le_bytes()
+    ).unwrap();
+    assert_eq!(app.deps.api.human_address(&token.owner).unwrap(), app.get_addr("ft").address);
+    app.change_env("user0", "ft");
+    let retrieve = ft::msg::HandleMsg::RetrievePropStake { prop_id: 1u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), retrieve).unwrap();
+    app.change_env("user2", "ft");
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), withdraw(2)).unwrap();
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000));
+
+    // accepted offer sends the NFT to the buyer, and the offer becomes the sale proceeds
+    make_offer(&mut app, 1_200, 300);
+    propose(&mut app, "user0", accept(3), 2).unwrap();
+    vote_proposal(&mut app, "user0", 2u32, Vote::Yes).unwrap();
+    vote_proposal(&mut app, "user1", 2u32, Vote::Yes).unwrap();
+    app.next_block(200);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 2u32 };
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+    app.change_env("ft", "s721");
+    let msg = extract_cmsg_check_env::<s721::msg::HandleMsg>(&app, &handle_resp.messages[0]).unwrap();
+    s721::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let token: s721::token::Token = json_load(
+        &ReadonlyPrefixedStorage::new(PREFIX_INFOS, &app.deps.storage), &0u32.to_le_bytes()
+    ).unwrap();
+    assert_eq!(app.deps.api.human_address(&token.owner).unwrap(), app.get_addr("user2").address);
+    assert!(!ftoken_info_r(&app.deps.storage).load().unwrap().vault_active);
+    app.change_env("user2", "ft");
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), withdraw(3)));
+    assert!(error.contains("buyout offer has been accepted"));
+
+    // user0 claims with 6 liquid ftokens, 60 staked and 4 in retrievable proposal stakes, for a 
+    // share of 840 (rounded down)
+    app.change_env("user0", "ft");
+    let set_vk = ft::msg::HandleMsg::SetViewingKey { key: "vkey".to_string(), padding: None };
+    ft::contract::handle(&mut app.deps, app.env.clone(), set_vk).unwrap();
+    let unclaimed = |app: &App| {
+        let msg = ft::msg::QueryMsg::FtokenVkQuery { 
+            address: app.get_addr("user0").address, 
+            key: "vkey".to_string(), 
+            query: ft::ftoken_mod::msg::FtokenAuthQuery::UnclaimedProceeds {  },
+        };
+        match from_binary(&ft::contract::query(&app.deps, msg).unwrap()).unwrap() {
+            ft::msg::QueryAnswer::FtokenQueryAnswer(answer) => answer,
+            _ => panic!("unexpected query answer"),
+        }
+    };
+    assert_eq!(unclaimed(&app), ft::ftoken_mod::msg::FtokenQueryAnswer::UnclaimedProceeds { 
+        ftokens: Uint128(70), proceeds: Uint128(839), claimed: ProceedsClaim::default(),
+    });
+    sim_claim_proceeds(&mut app, "user0").unwrap();
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000 + 839));
+
+    // claimed ftokens are burned, and the claim is recorded
+    assert_eq!(unclaimed(&app), ft::ftoken_mod::msg::FtokenQueryAnswer::UnclaimedProceeds { 
+        ftokens: Uint128(0), proceeds: Uint128(0), 
+        claimed: ProceedsClaim { ftokens: Uint128(70), proceeds: Uint128(839) },
+    });
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(0));
+    let msg = ft::msg::QueryMsg::TransactionHistory { 
+        address: app.get_addr("user0").address, key: "vkey".to_string(), page: None, page_size: 1,
+    };
+    match from_binary(&ft::contract::query(&app.deps, msg).unwrap()).unwrap() {
+        ft::msg::QueryAnswer::TransactionHistory { txs, .. } => {
+            assert_eq!(txs[0].coins.amount, Uint128(6));
+            assert_eq!(txs[0].memo, Some("claimed sale proceeds".to_string()));
+        },
+        _ => panic!("unexpected query answer"),
+    }
+    let total_supply = ft::state::ReadonlyConfig::from_storage(&app.deps.storage).total_supply();
+    assert_eq!(total_supply, 30);
+    let error = extract_error_msg(sim_claim_proceeds(&mut app, "user0"));
+    assert!(error.contains("you have no ftokens to claim proceeds with"));
+}
+
+#[test]
+fn test_median_resv_price() {
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.auc_conf.resv_agg = ResvAgg::Median;
+    fractionalize_with_conf(&mut app, config.clone());
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+
+    for (user, price) in [("user0", 400), ("user1", 2_000)] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(price) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+
+    // the median is not dragged towards user1's vote, unlike the mean
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::ReservationPrice {  }).unwrap();
+    assert_eq!(answer, ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPrice { 
+        ftokens_voted: Uint128(90), reservation_price: Uint128(400), method: ResvAgg::Median,
+    });
+    let error = extract_error_msg(sim_bid(&mut app, 399, Some("user2")));
+    assert!(error.contains("bid must be equal or greater than the reservation price of 400"));
+
+    // the DAO can switch to the mean
+    config.auc_conf.resv_agg = ResvAgg::Mean;
+    change_config(&mut app, "user0", config.clone()).unwrap();
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::ReservationPrice {  }).unwrap();
+    assert_eq!(answer, ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPrice { 
+        ftokens_voted: Uint128(90), reservation_price: Uint128(933), method: ResvAgg::Mean,
+    });
+
+    // unstaking removes stake from the median too
+    config.auc_conf.resv_agg = ResvAgg::Median;
+    change_config(&mut app, "user0", config).unwrap();
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(60) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let answer = ftoken_query(&app, ft::ftoken_mod::msg::FtokenQuery::ReservationPrice {  }).unwrap();
+    assert_eq!(answer, ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPrice { 
+        ftokens_voted: Uint128(30), reservation_price: Uint128(2_000), method: ResvAgg::Median,
+    });
+}
+
+#[test]
+fn test_resv_price_history() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+
+    // height 10: user0 votes. Height 20: user1 votes twice in the same block
+    for (blocks, user, price) in [(10, "user0", 400), (10, "user1", 1_000), (0, "user1", 700)] {
+        app.next_block(blocks);
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(price) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+
+    // height 30: user0 unstakes, which removes its vote
+    app.next_block(10);
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::Unstake { amount: Uint128(60) };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+
+    // staking without a vote does not add a checkpoint, and a later change in the same block 
+    // overwrites the earlier checkpoint
+    let checkpoint = |height, price, stake| ResvCheckpoint { height, price: Uint128(price), stake: Uint128(stake) };
+    let query = ft::ftoken_mod::msg::FtokenQuery::ReservationPriceHistory { page: 0, page_size: 10 };
+    assert_eq!(ftoken_query(&app, query).unwrap(), ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPriceHistory { 
+        checkpoints: vec![checkpoint(30, 700, 30), checkpoint(20, 500, 90), checkpoint(10, 400, 60), checkpoint(0, 500, 0)],
+        total_checkpoints: 4,
+    });
+    let query = ft::ftoken_mod::msg::FtokenQuery::ReservationPriceHistory { page: 1, page_size: 3 };
+    assert_eq!(ftoken_query(&app, query).unwrap(), ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPriceHistory { 
+        checkpoints: vec![checkpoint(0, 500, 0)],
+        total_checkpoints: 4,
+    });
+
+    // (500*10 + 400*10 + 500*10 + 700*10) / 40 
+    let query = ft::ftoken_mod::msg::FtokenQuery::ReservationPriceTwap { start_height: 0, end_height: 40 };
+    assert_eq!(ftoken_query(&app, query).unwrap(), ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPriceTwap { twap: Uint128(525) });
+    // (400*5 + 500*10 + 700*3) / 18
+    let query = ft::ftoken_mod::msg::FtokenQuery::ReservationPriceTwap { start_height: 15, end_height: 33 };
+    assert_eq!(ftoken_query(&app, query).unwrap(), ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPriceTwap { twap: Uint128(505) });
+    let query = ft::ftoken_mod::msg::FtokenQuery::ReservationPriceTwap { start_height: 50, end_height: 60 };
+    assert_eq!(ftoken_query(&app, query).unwrap(), ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPriceTwap { twap: Uint128(700) });
+    let query = ft::ftoken_mod::msg::FtokenQuery::ReservationPriceTwap { start_height: 20, end_height: 20 };
+    let error = extract_error_msg(ftoken_query(&app, query));
+    assert!(error.contains("end height must be greater than start height"));
+}
+
+#[test]
+fn test_resv_vote_expiry() {
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.auc_conf.resv_vote_lifetime = 20;
+    fractionalize_with_conf(&mut app, config);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+
+    let vote = |app: &mut App, user: &str, price: u128| {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(price) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    };
+    let expire = |app: &mut App, limit: u32| {
+        app.change_env("user2", "ft");
+        let msg = ft::msg::HandleMsg::ExpireResvVotes { limit };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    };
+    let resv_price = |app: &App| ftoken_query(app, ft::ftoken_mod::msg::FtokenQuery::ReservationPrice {  }).unwrap();
+    let exp_resv_price = |voted: u128, price: u128| ft::ftoken_mod::msg::FtokenQueryAnswer::ReservationPrice { 
+        ftokens_voted: Uint128(voted), reservation_price: Uint128(price), method: ResvAgg::Mean,
+    };
+
+    app.next_block(10);
+    vote(&mut app, "user0", 600);
+    app.next_block(15);
+    vote(&mut app, "user1", 500);
+
+    // user0's vote expires at height 30, but stays in the reservation price until removed
+    app.next_block(5);
+    assert_eq!(resv_price(&app), exp_resv_price(90, 566));
+    expire(&mut app, 10);
+    // the mean loses precision when votes are removed
+    assert_eq!(resv_price(&app), exp_resv_price(30, 499));
+    let error = extract_error_msg(sim_bid(&mut app, 600, Some("user2")));
+        assert!(error.contains("vault is not unlocked"));
+
+    // voting again renews the vote. The crank only removes as many votes as its limit
+    vote(&mut app, "user0", 600);
+    app.next_block(30);
+    expire(&mut app, 1);
+    assert_eq!(resv_price(&app), exp_resv_price(60, 599));
+
+    // bids only count live votes towards the unlock threshold
+    let error = extract_error_msg(sim_bid(&mut app, 600, Some("user2")));
+    assert!(error.contains("only 0 basis points of ftokens have voted"));
+    assert_eq!(resv_price(&app), exp_resv_price(0, 599));
+}
+
+#[test]
+fn test_stake_and_vote_via_send() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 10).unwrap();
+    let config = ftkn_config_r(&app.deps.storage).load().unwrap();
+    propose(&mut app, "user0", ft::ftoken_mod::msg::Proposal::ChangeConfig { config }, 2).unwrap();
+
+    // user1 stakes and votes on the proposal and the reservation price in one tx
+    app.next_block(1);
+    app.change_env("user1", "ft");
+    let receive_msg = ft::ftoken_mod::msg::FtokenReceiveMsg::Stake { 
+        proposal_vote: Some(ft::ftoken_mod::msg::ProposalVote { prop_id: 0, vote: Vote::Yes }), 
+        resv_price: Some(Uint128(600)),
+    };
+    let msg = ft::msg::HandleMsg::Send { 
+        recipient: app.get_addr("ft").address, 
+        recipient_code_hash: None, 
+        amount: Uint128(20), 
+        msg: Some(to_binary(&receive_msg).unwrap()), 
+        memo: None, 
+        padding: None,
+    };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    assert_eq!(ftoken_balance(&mut app, "user1"), Uint128(0));
+    let staked = ftkn_stake_r(&app.deps.storage).load(
+        &to_binary(&app.get_addr("user1").address).unwrap().as_slice()
+    ).unwrap();
+    assert_eq!(staked.amount, Uint128(30));
+
+    // proposal vote is capped at voting power at the snapshot height, reservation price vote counts all stake
+    let votes_total = votes_total_r(&app.deps.storage).load(&0u32.to_le_bytes()).unwrap();
+    assert_eq!(votes_total.yes, Uint128(10));
+    let agg_resv_price = agg_resv_price_r(&app.deps.storage).load().unwrap();
+    assert_eq!((agg_resv_price.uint128_stake(), agg_resv_price.uint128_price()), (Uint128(30), Uint128(600)));
+
+    // msg must be a valid ftoken receive msg
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::Send { 
+        recipient: app.get_addr("ft").address, 
+        recipient_code_hash: None, 
+        amount: Uint128(5), 
+        msg: Some(to_binary(&"stake").unwrap()), 
+        memo: None, 
+        padding: None,
+    };
+    assert!(ft::contract::handle(&mut app.deps, app.env.clone(), msg).is_err());
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(8));
+}
+
+#[test]
+fn test_distribute_proceeds() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 20).unwrap();
+
+    // cannot distribute before the vault closes
+    app.change_env("user2", "ft");
+    let msg = ft::msg::HandleMsg::DistributeProceeds { limit: 10 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()));
+    assert!(error.contains("vault is still active"));
+
+    // buyout
+    for user in ["user0", "user1"] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+    sim_bid(&mut app, 100, Some("user2")).unwrap();
+    app.next_block(100);
+    sim_finalize_auction(&mut app).unwrap();
+
+    // holders are paid in the order they first received ftokens: user0, user1, then this contract, 
+    // which holds staked ftokens on their behalf and is skipped
+    app.change_env("user2", "ft");
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), 
+        ft::msg::HandleMsg::DistributeProceeds { limit: 1 }).unwrap();
+    assert_eq!(handle_resp.messages.len(), 1);
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    // 70 out of 100 ftokens, rounded down by the fixed-point pro-rata calculation
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000 + 69));
+    assert_eq!(s20_balance(&mut app, "user1"), Uint128(5_000));
+    assert_eq!(proceeds_cursor_r(&app.deps.storage).load().unwrap(), 1);
+
+    // next call resumes from user1, who holds the remaining supply so receives the remaining proceeds
+    app.change_env("user2", "ft");
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
+    assert_eq!(handle_resp.messages.len(), 1);
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000 + 69));
+    assert_eq!(s20_balance(&mut app, "user1"), Uint128(5_000 + 31));
+    assert_eq!(ft::state::ReadonlyConfig::from_storage(&app.deps.storage).total_supply(), 0);
+
+    // nothing left to distribute or claim
+    app.change_env("user2", "ft");
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    assert_eq!(handle_resp.messages.len(), 0);
+    let error = extract_error_msg(sim_claim_proceeds(&mut app, "user0"));
+    assert!(error.contains("you have no ftokens to claim proceeds with"));
+}
+
+#[test]
+fn test_refund_outbid() {
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.auc_conf.refund_outbid = true;
+    fractionalize_with_conf(&mut app, config);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+    for user in ["user0", "user1"] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+
+    // first bid starts the auction, so there is no one to refund
+    let handle_resp = sim_bid(&mut app, 100, Some("user2")).unwrap();
+    assert_eq!(handle_resp.messages.len(), 1);
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000 - 100));
+
+    // user2 is refunded when outbid
+    let handle_resp = sim_bid(&mut app, 110, Some("user0")).unwrap();
+    assert_eq!(handle_resp.messages.len(), 2);
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000 - 110));
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000));
+    assert!(get_bid(&app.deps.storage, 0).unwrap().retrieved_bid);
+
+    // highest bidder raising their own bid only transfers the increment, and is not refunded
+    let handle_resp = sim_bid(&mut app, 121, Some("user0")).unwrap();
+    assert_eq!(handle_resp.messages.len(), 1);
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000 - 121));
+
+    // refunded bidder transfers their full bid when bidding again
+    sim_bid(&mut app, 134, Some("user2")).unwrap();
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000 - 134));
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000));
+    assert_eq!(s20_balance(&mut app, "ft"), Uint128(134));
+
+    // refunded bids cannot be retrieved again once the auction ends
+    app.next_block(100);
+    sim_finalize_auction(&mut app).unwrap();
+    let error = extract_error_msg(sim_retrieve_bid(&mut app, "user0"));
+    assert!(error.contains("you have already retrieved bid"));
+    let error = extract_error_msg(sim_retrieve_bid(&mut app, "user2"));
+    assert!(error.contains("you won the bid. You should have received the NFT"));
+}
+
+#[test]
+fn test_refund_losing_bids() {
+    let mut app = App::new();
+    init_default(&mut app);
+    fractionalize_default(&mut app);
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+    for user in ["user0", "user1"] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+
+    // bids: user2 100, user0 110 raised to 121, then user1 wins with 134
+    sim_bid(&mut app, 100, Some("user2")).unwrap();
+    sim_bid(&mut app, 110, Some("user0")).unwrap();
+    sim_bid(&mut app, 121, Some("user0")).unwrap();
+    sim_bid(&mut app, 134, Some("user1")).unwrap();
+
+    // cannot refund before the vault closes
+    app.change_env("user2", "ft");
+    let msg = ft::msg::HandleMsg::RefundLosingBids { limit: 10 };
+    let error = extract_error_msg(ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()));
+    assert!(error.contains("vault is still active"));
+
+    app.next_block(100);
+    sim_finalize_auction(&mut app).unwrap();
+
+    // user2's bid is refunded. user0's first bid is superseded by its raised bid, so is skipped
+    app.change_env("user2", "ft");
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), 
+        ft::msg::HandleMsg::RefundLosingBids { limit: 2 }).unwrap();
+    assert_eq!(handle_resp.messages.len(), 1);
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    assert_eq!(s20_balance(&mut app, "user2"), Uint128(5_000));
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000 - 121));
+    assert_eq!(bid_refund_cursor_r(&app.deps.storage).load().unwrap(), 2);
+
+    // next call resumes from user0's raised bid
+    app.change_env("user2", "ft");
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg.clone()).unwrap();
+    assert_eq!(handle_resp.messages.len(), 1);
+    sim_ft_msgs_to_s20(&mut app, &handle_resp).unwrap();
+    assert_eq!(s20_balance(&mut app, "user0"), Uint128(5_000));
+    assert_eq!(s20_balance(&mut app, "ft"), Uint128(134));
+
+    // refunded bids cannot be retrieved again, and further calls do nothing
+    let error = extract_error_msg(sim_retrieve_bid(&mut app, "user0"));
+    assert!(error.contains("you have already retrieved bid"));
+    app.change_env("user2", "ft");
+    let handle_resp = ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    assert_eq!(handle_resp.messages.len(), 0);
+}
+
+#[test]
+fn test_orphaned_ftokens() {
+    let mut app = App::new();
+    init_default(&mut app);
+    let mut config = default_ftkn_conf(&app);
+    config.prop_conf.slash_dest = SlashDest::VetoVoters;
+    fractionalize_with_conf(&mut app, config.clone());
+    transfer_ftkn_and_stake(&mut app, "user0", "user1", 30, 60, 30).unwrap();
+
+    // user1 vetoes a proposal whose stake goes to veto voters, but does not claim it yet
+    let proposal = ft::ftoken_mod::msg::Proposal::ChangeConfig { config };
+    propose(&mut app, "user0", proposal, 5).unwrap();
+    vote_proposal(&mut app, "user1", 0u32, Vote::Veto).unwrap();
+    app.next_block(200);
+    let finalize = ft::msg::HandleMsg::FinalizeExecuteProp { prop_id: 0u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), finalize).unwrap();
+
+    // ftokens transferred or sent to the contract directly cannot be retrieved by anyone
+    app.change_env("user0", "ft");
+    let msg = ft::msg::HandleMsg::Transfer { 
+        recipient: app.get_addr("ft").address, amount: Uint128(2), memo: None, padding: None,
+    };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let msg = ft::msg::HandleMsg::Send { 
+        recipient: app.get_addr("ft").address, recipient_code_hash: None, amount: Uint128(1), msg: None, memo: None, padding: None,
+    };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    assert_eq!(orphaned_ftkn_r(&app.deps.storage).load().unwrap(), Uint128(3));
+    assert_eq!(ftoken_balance(&mut app, "user0"), Uint128(2));
+
+    // close the vault
+    for user in ["user0", "user1"] {
+        app.change_env(user, "ft");
+        let msg = ft::msg::HandleMsg::VoteReservationPrice { resv_price: Uint128(100) };
+        ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    }
+    sim_bid(&mut app, 100, Some("user2")).unwrap();
+    app.next_block(100);
+    sim_finalize_auction(&mut app).unwrap();
+
+    // orphaned ftokens have no claim on the sale proceeds: user0 claims with 62 of 97 ftokens
+    let user0_before = s20_balance(&mut app, "user0");
+    sim_claim_proceeds(&mut app, "user0").unwrap();
+    assert_eq!(s20_balance(&mut app, "user0"), user0_before + Uint128(63));
+
+    // the slashed stake can still be claimed once the vault closes. user1 claims last with the 
+    // remaining 35 ftokens, so the sale proceeds are fully paid out
+    app.change_env("user1", "ft");
+    let msg = ft::msg::HandleMsg::ClaimSlashedStake { prop_id: 0u32 };
+    ft::contract::handle(&mut app.deps, app.env.clone(), msg).unwrap();
+    let user1_before = s20_balance(&mut app, "user1");
+    sim_claim_proceeds(&mut app, "user1").unwrap();
+    assert_eq!(s20_balance(&mut app, "user1"), user1_before + Uint128(100 - 63));
+    let sale_proceeds = sale_proceeds_r(&app.deps.storage).load().unwrap();
+    assert_eq!((sale_proceeds.claimed, sale_proceeds.claimed_ftokens), (Uint128(100), Uint128(97)));
+}
 
 // #[test]
 // fn test_bidding_retrievenft_forced() {
@@ -522,5 +2086,3 @@ fn test_proposal_votes() {
 
     
 // }
-
-
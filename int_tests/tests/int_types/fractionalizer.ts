@@ -87,13 +87,66 @@ interface AucConf {
     resv_boundary: u32,
     min_bid_inc: u32,
     unlock_threshold: Uint128,
+    resv_agg: ResvAgg,
+    resv_vote_lifetime: u64,
+    refund_outbid: boolean,
 }
 
+type ResvAgg = "mean" | "median";
+
 interface PropConf {
     min_stake: Uint128,
     vote_period: u64,
     vote_quorum: Uint128,
     veto_threshold: Uint128,
+    pass_threshold: Uint128,
+    threshold_overrides: ThresholdOverride[],
+    emergency_threshold: Uint128,
+    private_votes: boolean,
+    vote_scheme: VoteScheme,
+    slash_dest: SlashDest,
+    cancel_period: u64,
+    nft_msg_policies?: NftMsgPolicy[],
+}
+
+type VoteScheme = 
+    | "linear"
+    | "quadratic"
+    | { conviction: { max_multiplier: u8 } };
+
+type SlashDest = "burn" | "treasury" | "veto_voters";
+
+type PropType = "msg_to_nft" 
+    | "change_config" 
+    | "register_treasury_token" 
+    | "treasury_spend" 
+    | "rotate_viewing_key" 
+    | "revoke_permits"
+    | "set_vault_status"
+    | "accept_offer";
+
+type NftMsgType = "set_metadata"
+    | "set_royalty_info"
+    | "reveal"
+    | "make_ownership_private"
+    | "set_global_approval"
+    | "set_whitelisted_approval"
+    | "revoke"
+    | "revoke_all"
+    | "revoke_permit";
+
+interface NftMsgPolicy {
+    msg_type: NftMsgType,
+    vote_quorum?: Uint128,
+    pass_threshold?: Uint128,
+    veto_threshold?: Uint128,
+}
+
+interface ThresholdOverride {
+    prop_type: PropType,
+    vote_quorum?: Uint128,
+    pass_threshold?: Uint128,
+    veto_threshold?: Uint128,
 }
 
 interface FtokenConf {
@@ -101,6 +154,7 @@ interface FtokenConf {
     priv_metadata_view_threshold: u32,
     auc_conf: AucConf,
     prop_conf: PropConf,
+    guardians: HumanAddr[],
 }
 
 interface FtokenInit {
@@ -2,7 +2,7 @@ import * as Snip20 from "secretjs/src/extensions/snip20/types";
 // import * as Snip721 from "secretjs/src/extensions/snip721/types";
 import Metadata  from "./metadata";
 import FtokenConf from "./ftokenconf";
-import { HumanAddr, Uint128, Binary, u8, u32, u64, UndrNftInfo } from "./utils";
+import { ContractInfo, HumanAddr, Uint128, Binary, u8, u16, u32, u64, UndrNftInfo } from "./utils";
 
 /////////////////////////////////////////////////////////////////////////////////
 // Instantiation message
@@ -70,6 +70,8 @@ interface Unstake {
     amount: Uint128,
 }
 
+interface ClaimUnbonded { }
+
 interface VoteProposal {
     prop_id: u32,
     vote: Vote,
@@ -79,9 +81,20 @@ interface FinalizeAuction { }
 
 interface RetrieveBid { }
 
+interface RefundLosingBids {
+    limit: u32,
+}
+
 interface ClaimProceeds { }
 
+interface DistributeProceeds {
+    limit: u32,
+}
+
 interface Propose {
+    title: string,
+    description: string,
+    link?: string,
     proposal: Proposal,
     stake: Uint128,
 }
@@ -98,6 +111,57 @@ interface VoteReservationPrice {
     resv_price: Uint128,
 }
 
+interface ExpireResvVotes {
+    limit: u32,
+}
+
+interface Delegate {
+    proposals?: HumanAddr,
+    resv_price?: HumanAddr,
+}
+
+interface TreasuryDeposit {
+    amount: Uint128,
+}
+
+interface ClaimSlashedStake {
+    prop_id: u32,
+}
+
+interface CancelProposal {
+    prop_id: u32,
+}
+
+type VaultStatus = "normal" | "stop_auction" | "stop_governance" | "stop_all";
+
+interface SetVaultStatus {
+    status: VaultStatus,
+}
+
+interface MakeTenderOffer {
+    amount: Uint128,
+    price: Uint128,
+    period: u64,
+}
+
+interface Tender {
+    offer_id: u32,
+    amount: Uint128,
+}
+
+interface SettleTenderOffer {
+    offer_id: u32,
+}
+
+interface MakeBuyoutOffer {
+    amount: Uint128,
+    period: u64,
+}
+
+interface WithdrawBuyoutOffer {
+    offer_id: u32,
+}
+
 export type FtokenHandleMsg = Snip20.Snip20DecreaseAllowanceOptions
     | Snip20.Snip20IncreaseAllowanceOptions
     | Snip20.Snip20SendOptions
@@ -108,17 +172,39 @@ export type FtokenHandleMsg = Snip20.Snip20DecreaseAllowanceOptions
     | Bid
     | Stake
     | Unstake
+    | ClaimUnbonded
     | VoteProposal
     | FinalizeAuction
     | RetrieveBid
+    | RefundLosingBids
     | ClaimProceeds
+    | DistributeProceeds
     | Propose
     | FinalizeExecuteProp
     | RetrievePropStake
     | VoteReservationPrice
+    | ExpireResvVotes
+    | Delegate
+    | TreasuryDeposit
+    | ClaimSlashedStake
+    | CancelProposal
+    | SetVaultStatus
+    | MakeTenderOffer
+    | Tender
+    | SettleTenderOffer
+    | MakeBuyoutOffer
+    | WithdrawBuyoutOffer
 
 export type FtokenHandleResponse = {}; // todo
 
+/** msg attached to a `send` of ftokens to the ftoken contract */
+export interface FtokenReceiveMsg {
+    stake: {
+        proposal_vote?: { prop_id: u32, vote: Vote },
+        resv_price?: Uint128,
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////////
 // Query mesages
 /////////////////////////////////////////////////////////////////////////////////
@@ -178,7 +264,41 @@ interface SetWhitelistedApproval {
     expires?: Expiration,
 }
 
-type AllowedNftMsg = SetMetadata | Reveal | MakeOwnershipPrivate | SetGlobalApproval | SetWhitelistedApproval
+interface Royalty {
+    recipient: HumanAddr,
+    rate: u16,
+}
+
+interface RoyaltyInfo {
+    decimal_places_in_rates: u8,
+    royalties: Royalty[],
+}
+
+interface SetRoyaltyInfo {
+    royalty_info?: RoyaltyInfo,
+}
+
+interface Revoke {
+    spender: HumanAddr,
+}
+
+interface RevokeAll {
+    operator: HumanAddr,
+}
+
+interface RevokePermit {
+    permit_name: string,
+}
+
+type AllowedNftMsg = SetMetadata 
+    | Reveal 
+    | MakeOwnershipPrivate 
+    | SetGlobalApproval 
+    | SetWhitelistedApproval
+    | SetRoyaltyInfo
+    | Revoke
+    | RevokeAll
+    | RevokePermit
 
 interface MsgToNft {
     msg: AllowedNftMsg,
@@ -188,5 +308,34 @@ interface ChangeConfig {
     config: FtokenConf,
 }
 
-type Proposal = MsgToNft | ChangeConfig;
+interface RegisterTreasuryToken {
+    token: ContractInfo,
+}
+
+interface Payout {
+    recipient: HumanAddr,
+    amount: Uint128,
+}
+
+interface TreasurySpend {
+    token?: HumanAddr,
+    payouts: Payout[],
+}
+
+interface RotateViewingKey { }
+
+interface RevokePermits {
+    permit_names: string[],
+}
+
+interface SetVaultStatusProp {
+    status: VaultStatus,
+}
+
+interface AcceptOffer {
+    offer_id: u32,
+}
+
+type Proposal = MsgToNft | ChangeConfig | RegisterTreasuryToken | TreasurySpend | RotateViewingKey | RevokePermits 
+    | SetVaultStatusProp | AcceptOffer;
 
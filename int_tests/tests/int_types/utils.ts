@@ -2,6 +2,7 @@ export type HumanAddr = string;
 export type Uint128 = string;
 export type Binary = string;
 export type u8 = number;
+export type u16 = number;
 export type u32 = number;
 export type u64 = number;
 